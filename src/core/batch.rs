@@ -0,0 +1,125 @@
+#![allow(dead_code)]
+
+// Structure-of-arrays copy of the players for hot loops (rollouts, shot and pass checks). Every
+// query runs one fixed-length loop over flat f32 arrays without square roots where it can, which
+// the compiler vectorizes, and answers "which players" with a bit mask instead of allocating.
+
+use super::config::*;
+use super::state::*;
+use super::util::*;
+
+pub const BATCH_LEN: usize = NUM_PLAYERS as usize * 2;
+
+// Bit `id` is set for player `id`
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct PlayerMask(pub u32);
+
+impl PlayerMask {
+    pub const NONE: Self = PlayerMask(0);
+
+    pub fn team(team: Team) -> Self {
+        let ours = (1u32 << NUM_PLAYERS) - 1;
+        match team {
+            Team::Me => PlayerMask(ours),
+            Team::Other => PlayerMask(ours << NUM_PLAYERS),
+        }
+    }
+
+    pub fn contains(&self, id: PlayerId) -> bool {
+        self.0 & (1 << id) != 0
+    }
+
+    pub fn without(self, id: PlayerId) -> Self {
+        PlayerMask(self.0 & !(1 << id))
+    }
+
+    pub fn and(self, other: PlayerMask) -> Self {
+        PlayerMask(self.0 & other.0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn count(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = PlayerId> {
+        let bits = self.0;
+        (0..BATCH_LEN as PlayerId).filter(move |&id| bits & (1 << id) != 0)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlayerBatch {
+    pub x: [f32; BATCH_LEN],
+    pub y: [f32; BATCH_LEN],
+    pub speed: [f32; BATCH_LEN],
+    pub pickup_radius: [f32; BATCH_LEN],
+}
+
+impl PlayerBatch {
+    pub fn new(players: &PlayerArray<PlayerState>) -> Self {
+        Self {
+            x: players.each_ref().map(|p| p.pos.x),
+            y: players.each_ref().map(|p| p.pos.y),
+            speed: players.each_ref().map(|p| p.speed),
+            pickup_radius: players.each_ref().map(|p| p.pickup_radius),
+        }
+    }
+
+    pub fn dist_sq_to(&self, point: Vec2) -> [f32; BATCH_LEN] {
+        let mut out = [0.0; BATCH_LEN];
+        for (i, d) in out.iter_mut().enumerate() {
+            let (dx, dy) = (self.x[i] - point.x, self.y[i] - point.y);
+            *d = dx * dx + dy * dy;
+        }
+        out
+    }
+
+    pub fn dist_to(&self, point: Vec2) -> [f32; BATCH_LEN] {
+        self.dist_sq_to(point).map(f32::sqrt)
+    }
+
+    // Closest player of a team to `point` and its distance
+    pub fn nearest(&self, team: Team, point: Vec2) -> (PlayerId, f32) {
+        let dist_sq = self.dist_sq_to(point);
+        let offset = match team {
+            Team::Me => 0,
+            Team::Other => NUM_PLAYERS as usize,
+        };
+        let mut best = offset;
+        for i in offset..offset + NUM_PLAYERS as usize {
+            if dist_sq[i] < dist_sq[best] {
+                best = i;
+            }
+        }
+        (best as PlayerId, dist_sq[best].sqrt())
+    }
+
+    pub fn nearest_per_team(&self, point: Vec2) -> TeamPair<(PlayerId, f32)> {
+        TeamPair::new(self.nearest(Team::Me, point), self.nearest(Team::Other, point))
+    }
+
+    pub fn within(&self, point: Vec2, radius: f32) -> PlayerMask {
+        let dist_sq = self.dist_sq_to(point);
+        let radius_sq = radius * radius;
+        let mut bits = 0;
+        for (i, d) in dist_sq.iter().enumerate() {
+            bits |= ((*d <= radius_sq) as u32) << i;
+        }
+        PlayerMask(bits)
+    }
+
+    // Players that can get `point` within pickup reach in `ticks` ticks at full speed
+    pub fn reaching(&self, point: Vec2, ticks: f32) -> PlayerMask {
+        let dist_sq = self.dist_sq_to(point);
+        let mut bits = 0;
+        for (i, d) in dist_sq.iter().enumerate() {
+            let reach = self.pickup_radius[i] + self.speed[i] * ticks;
+            bits |= ((*d <= reach * reach) as u32) << i;
+        }
+        PlayerMask(bits)
+    }
+}
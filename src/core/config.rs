@@ -46,9 +46,23 @@ pub struct GoalConfig {
 }
 
 
+impl BallConfig {
+    // fraction of its velocity a free ball keeps from one tick to the next
+    pub fn decay(&self) -> f32 {
+        1.0 - self.friction
+    }
+}
+
+impl PlayerConfig {
+    // speed of a player while carrying the ball
+    pub fn carrier_speed(&self) -> f32 {
+        self.speed * self.possession_slowdown
+    }
+}
+
 impl GoalConfig {
     pub fn current_height(&self, conf: &GameConfig, tick: u32) -> u32 {
-        if tick <= conf.max_ticks {
+        if !conf.is_endgame(tick) {
             self.normal_height
         } else {
             conf.field.height
@@ -68,6 +82,13 @@ pub struct GameConfig {
     pub goal: GoalConfig,
 }
 
+impl GameConfig {
+    // past `max_ticks` both goals span the whole field height
+    pub fn is_endgame(&self, tick: u32) -> bool {
+        tick > self.max_ticks
+    }
+}
+
 impl FieldConfig {
     pub fn center(&self) -> Vec2 {
         Vec2::new(self.width as f32 * 0.5, self.height as f32 * 0.5)
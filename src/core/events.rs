@@ -0,0 +1,168 @@
+#![allow(dead_code)]
+
+// Turns consecutive states into what happened between them: possession changes, passes and
+// what became of them, tackles, goals, stagnation resets and the start of endgame. Feed it
+// every state from `on_tick` (or of a recorded match), in order.
+//
+// Only the states are looked at, so a few things are inferred the way `core::sim` plays them:
+// a carrier changing hands before its capture window ran out must have passed, and a kickoff
+// that is neither a goal nor endgame after the ball sat still long enough is a stagnation reset.
+
+use serde::{ Serialize, Deserialize };
+use super::config::*;
+use super::state::*;
+use super::util::*;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum GameEventKind {
+    PossessionGained { player: PlayerId, team: Team },
+    PossessionLost { player: PlayerId, team: Team },
+    // the ball was taken off the carrier once its capture window was over
+    Tackle { by: PlayerId, from: PlayerId },
+    PassStarted { passer: PlayerId, team: Team, dir: Vec2 },
+    PassCompleted { passer: PlayerId, receiver: PlayerId, team: Team },
+    // `team` is the one that passed
+    PassIntercepted { passer: PlayerId, by: PlayerId, team: Team },
+    // the ball stopped with nobody picking it up
+    PassRanOut { passer: PlayerId, team: Team },
+    Goal { team: Team, score: TeamPair<u32> },
+    StagnationReset,
+    EndgameStarted,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct GameEvent {
+    pub tick: u32,
+    pub kind: GameEventKind,
+}
+
+pub type EventListener = Box<dyn FnMut(&GameEvent)>;
+
+#[derive(Default)]
+pub struct EventDetector {
+    prev: Option<GameState>,
+    // passer and team of the ball in flight
+    in_flight: Option<(PlayerId, Team)>,
+    listeners: Vec<EventListener>,
+}
+
+impl EventDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Called with every event, in order, as `update` finds them
+    pub fn subscribe(&mut self, listener: impl FnMut(&GameEvent) + 'static) {
+        self.listeners.push(Box::new(listener));
+    }
+
+    // Forgets the previous state, listeners are kept
+    pub fn clear(&mut self) {
+        self.prev = None;
+        self.in_flight = None;
+    }
+
+    pub fn update(&mut self, state: &GameState, conf: &GameConfig) -> Vec<GameEvent> {
+        // a tick going backwards means a new match
+        if self.prev.as_ref().is_some_and(|prev| state.tick < prev.tick) {
+            self.clear();
+        }
+        let Some(prev) = self.prev.replace(state.clone()) else {
+            return Vec::new();
+        };
+
+        let mut kinds = Vec::new();
+        self.detect(&prev, state, conf, &mut kinds);
+        let events: Vec<GameEvent> = kinds.into_iter().map(|kind| GameEvent { tick: state.tick, kind }).collect();
+        for event in &events {
+            self.listeners.iter_mut().for_each(|listener| listener(event));
+        }
+        events
+    }
+
+    fn detect(&mut self, prev: &GameState, cur: &GameState, conf: &GameConfig, out: &mut Vec<GameEventKind>) {
+        let team_of = |id: PlayerId| cur.player_team(id).unwrap_or(Team::Me);
+
+        for team in [Team::Me, Team::Other] {
+            if cur.score[team] > prev.score[team] {
+                out.push(GameEventKind::Goal { team, score: cur.score });
+            }
+        }
+        let goal = cur.score != prev.score;
+        // the field is reset for endgame once `max_ticks` is reached
+        let endgame = prev.tick < conf.max_ticks && cur.tick >= conf.max_ticks;
+        if endgame {
+            out.push(GameEventKind::EndgameStarted);
+        }
+        let center = conf.field.center();
+        let kickoff = cur.ball.pos == center && cur.ball_stagnation.center == center && cur.ball_stagnation.tick == cur.tick;
+        let stalled = cur.tick.saturating_sub(prev.ball_stagnation.tick) >= conf.ball.stagnation_ticks;
+        if kickoff && stalled && !goal && !endgame {
+            out.push(GameEventKind::StagnationReset);
+        }
+        if goal || endgame || (kickoff && stalled) {
+            if let Some(owner) = prev.ball_owner() {
+                out.push(GameEventKind::PossessionLost { player: owner, team: team_of(owner) });
+            }
+            self.in_flight = None;
+            return;
+        }
+
+        let (before, after) = (prev.ball_owner(), cur.ball_owner());
+        if let Some(owner) = before.filter(|&owner| after != Some(owner)) {
+            out.push(GameEventKind::PossessionLost { player: owner, team: team_of(owner) });
+        }
+
+        // a carrier still inside its capture window can only lose the ball by passing it
+        if let (
+            BallPossessionState::Possessed { owner, team, capture_ticks },
+            Some(receiver),
+        ) = (&prev.ball_possession, after) {
+            if receiver != *owner && *capture_ticks < conf.ball.capture_ticks {
+                self.in_flight = Some((*owner, *team));
+                out.push(GameEventKind::PassStarted { passer: *owner, team: *team, dir: (cur.ball.pos - prev.ball.pos).normalize_or_zero() });
+            }
+        }
+
+        if let BallPossessionState::Passing { team } = cur.ball_possession {
+            if !matches!(prev.ball_possession, BallPossessionState::Passing { .. }) {
+                // a pickup and a pass within one tick leave no carrier behind, take whoever of the
+                // team was closest to the ball
+                let passer = before.filter(|&owner| team_of(owner) == team).or_else(|| {
+                    prev.teams()[team]
+                        .iter()
+                        .min_by(|a, b| a.pos.dist_sq(&prev.ball.pos).total_cmp(&b.pos.dist_sq(&prev.ball.pos)))
+                        .map(|player| player.id)
+                });
+                if let Some(passer) = passer {
+                    self.in_flight = Some((passer, team));
+                    out.push(GameEventKind::PassStarted { passer, team, dir: cur.ball.vel.normalize_or_zero() });
+                }
+            }
+        }
+
+        if let Some(receiver) = after.filter(|&owner| before != Some(owner)) {
+            let team = team_of(receiver);
+            match self.in_flight.take() {
+                Some((passer, from)) if from == team => {
+                    out.push(GameEventKind::PassCompleted { passer, receiver, team });
+                },
+                Some((passer, from)) => {
+                    out.push(GameEventKind::PassIntercepted { passer, by: receiver, team: from });
+                },
+                None => {
+                    if let Some(from) = before.filter(|&owner| team_of(owner) != team) {
+                        out.push(GameEventKind::Tackle { by: receiver, from });
+                    }
+                },
+            }
+            out.push(GameEventKind::PossessionGained { player: receiver, team });
+        }
+
+        if cur.is_ball_free() {
+            if let Some((passer, team)) = self.in_flight.take() {
+                out.push(GameEventKind::PassRanOut { passer, team });
+            }
+        }
+    }
+}
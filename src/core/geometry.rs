@@ -0,0 +1,408 @@
+#![allow(dead_code)]
+
+// Shapes on top of `Vec2` and the tests between them that analyses keep needing: segments,
+// rays, circles and axis aligned rectangles, plus the field, goal mouths and penalty boxes built
+// straight from the config. Directions are not required to be normalized unless noted.
+
+use serde::{ Serialize, Deserialize };
+use super::config::*;
+use super::state::Team;
+use super::util::*;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Segment {
+    pub a: Vec2,
+    pub b: Vec2,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Ray {
+    pub origin: Vec2,
+    // normalized
+    pub dir: Vec2,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Circle {
+    pub center: Vec2,
+    pub radius: f32,
+}
+
+// Axis aligned, `min` is the top left corner
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Rect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Segment {
+    pub fn new(a: Vec2, b: Vec2) -> Self {
+        Self { a, b }
+    }
+
+    pub fn length(&self) -> f32 {
+        self.a.dist(&self.b)
+    }
+
+    pub fn midpoint(&self) -> Vec2 {
+        (self.a + self.b) * 0.5
+    }
+
+    // `a` at 0, `b` at 1
+    pub fn point_at(&self, t: f32) -> Vec2 {
+        self.a + (self.b - self.a) * t
+    }
+
+    // Parameter of the point of the segment closest to `pos`, in [0, 1]
+    pub fn closest_t(&self, pos: Vec2) -> f32 {
+        let along = self.b - self.a;
+        let len_sq = along.norm_sq();
+        if len_sq < EPSILON * EPSILON {
+            return 0.0;
+        }
+        ((pos - self.a).dot(along) / len_sq).clamp(0.0, 1.0)
+    }
+
+    pub fn closest_point(&self, pos: Vec2) -> Vec2 {
+        self.point_at(self.closest_t(pos))
+    }
+
+    pub fn dist(&self, pos: Vec2) -> f32 {
+        pos.dist(&self.closest_point(pos))
+    }
+
+    pub fn intersects_circle(&self, circle: &Circle) -> bool {
+        self.dist(circle.center) <= circle.radius
+    }
+
+    // First point where the segment, walked from `a`, enters the circle, `a` itself when it starts
+    // inside
+    pub fn circle_entry(&self, circle: &Circle) -> Option<Vec2> {
+        if circle.contains(self.a) {
+            return Some(self.a);
+        }
+        let len = self.length();
+        let ray = Ray::new(self.a, self.b - self.a)?;
+        ray.intersect_circle(circle).filter(|&t| t <= len).map(|t| ray.at(t))
+    }
+
+    // Crossing point of two segments, none when they are parallel
+    pub fn intersect(&self, other: &Segment) -> Option<Vec2> {
+        let r = self.b - self.a;
+        let s = other.b - other.a;
+        let denom = r.cross(s);
+        if denom.abs() < EPSILON * EPSILON {
+            return None;
+        }
+        let diff = other.a - self.a;
+        let t = diff.cross(s) / denom;
+        let u = diff.cross(r) / denom;
+        ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then(|| self.point_at(t))
+    }
+
+    // A team's goal mouth at its normal height, lower y first. Our goal is on the left.
+    pub fn goal_mouth(field: &FieldConfig, goal: &GoalConfig, team: Team) -> Self {
+        Self::goal_line(field, team, goal.normal_height as f32)
+    }
+
+    // Same, at the height the goal has at `tick`, the whole back line in endgame
+    pub fn goal_mouth_at(conf: &GameConfig, team: Team, tick: u32) -> Self {
+        Self::goal_line(&conf.field, team, conf.goal.current_height(conf, tick) as f32)
+    }
+
+    fn goal_line(field: &FieldConfig, team: Team, height: f32) -> Self {
+        let center = match team {
+            Team::Me => field.goal_self(),
+            Team::Other => field.goal_other(),
+        };
+        let half = Vec2::new(0.0, height * 0.5);
+        Self::new(center - half, center + half)
+    }
+}
+
+impl Ray {
+    // None for a zero direction
+    pub fn new(origin: Vec2, dir: Vec2) -> Option<Self> {
+        let len = dir.norm();
+        (len >= EPSILON).then(|| Self { origin, dir: dir / len })
+    }
+
+    pub fn at(&self, t: f32) -> Vec2 {
+        self.origin + self.dir * t
+    }
+
+    // Distance along the ray to where it first enters the circle, 0 when it starts inside
+    pub fn intersect_circle(&self, circle: &Circle) -> Option<f32> {
+        let to = circle.center - self.origin;
+        let c = to.norm_sq() - circle.radius * circle.radius;
+        if c <= 0.0 {
+            return Some(0.0);
+        }
+        let b = to.dot(self.dir);
+        let disc = b * b - c;
+        if b <= 0.0 || disc < 0.0 {
+            return None;
+        }
+        Some(b - disc.sqrt())
+    }
+
+    // Distance along the ray to where it crosses the segment
+    pub fn intersect_segment(&self, segment: &Segment) -> Option<f32> {
+        let s = segment.b - segment.a;
+        let denom = self.dir.cross(s);
+        if denom.abs() < EPSILON * EPSILON {
+            return None;
+        }
+        let diff = segment.a - self.origin;
+        let t = diff.cross(s) / denom;
+        let u = diff.cross(self.dir) / denom;
+        (t >= 0.0 && (0.0..=1.0).contains(&u)).then_some(t)
+    }
+}
+
+impl Circle {
+    pub fn new(center: Vec2, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    pub fn contains(&self, pos: Vec2) -> bool {
+        self.center.dist_sq(&pos) <= self.radius * self.radius
+    }
+
+    pub fn intersects(&self, other: &Circle) -> bool {
+        let reach = self.radius + other.radius;
+        self.center.dist_sq(&other.center) <= reach * reach
+    }
+
+    pub fn closest_point(&self, pos: Vec2) -> Vec2 {
+        self.center + (pos - self.center).normalize_or_zero() * self.radius
+    }
+}
+
+impl Rect {
+    // Corners in any order
+    pub fn new(a: Vec2, b: Vec2) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    pub fn field(field: &FieldConfig) -> Self {
+        Self::new(Vec2::ZERO, field.bottom_right())
+    }
+
+    // The box in front of a team's goal, without its rounded corners
+    pub fn penalty_box(field: &FieldConfig, goal: &GoalConfig, team: Team) -> Self {
+        let half = goal.penalty_box_height as f32 * 0.5;
+        let width = goal.penalty_box_width as f32;
+        let center_y = field.height as f32 * 0.5;
+        let (x0, x1) = match team {
+            Team::Me => (0.0, width),
+            Team::Other => (field.width as f32 - width, field.width as f32),
+        };
+        Self::new(Vec2::new(x0, center_y - half), Vec2::new(x1, center_y + half))
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) * 0.5
+    }
+
+    // Shrunk by `margin` on every side, e.g. the area a player's center can reach
+    pub fn inset(&self, margin: f32) -> Self {
+        let margin = margin.min(self.size().x * 0.5).min(self.size().y * 0.5);
+        Self { min: self.min + Vec2::new(margin, margin), max: self.max - Vec2::new(margin, margin) }
+    }
+
+    pub fn contains(&self, pos: Vec2) -> bool {
+        pos.x >= self.min.x && pos.x <= self.max.x && pos.y >= self.min.y && pos.y <= self.max.y
+    }
+
+    pub fn closest_point(&self, pos: Vec2) -> Vec2 {
+        pos.max(self.min).min(self.max)
+    }
+
+    pub fn dist(&self, pos: Vec2) -> f32 {
+        pos.dist(&self.closest_point(pos))
+    }
+
+    // Distance along a ray starting inside to where it leaves, and the normal of the wall it
+    // leaves through
+    pub fn exit(&self, ray: &Ray) -> Option<(f32, Vec2)> {
+        let mut best: Option<(f32, Vec2)> = None;
+        let mut wall = |t: f32, normal: Vec2| {
+            if t >= 0.0 && best.map_or(true, |(it, _)| t < it) {
+                best = Some((t, normal));
+            }
+        };
+        if ray.dir.x > EPSILON {
+            wall((self.max.x - ray.origin.x) / ray.dir.x, Vec2::new(-1.0, 0.0));
+        }
+        if ray.dir.x < -EPSILON {
+            wall((self.min.x - ray.origin.x) / ray.dir.x, Vec2::new(1.0, 0.0));
+        }
+        if ray.dir.y > EPSILON {
+            wall((self.max.y - ray.origin.y) / ray.dir.y, Vec2::new(0.0, -1.0));
+        }
+        if ray.dir.y < -EPSILON {
+            wall((self.min.y - ray.origin.y) / ray.dir.y, Vec2::new(0.0, 1.0));
+        }
+        best
+    }
+
+    // The ray bouncing off the wall it leaves through, starting at the wall
+    pub fn reflect(&self, ray: &Ray) -> Option<Ray> {
+        let (t, normal) = self.exit(ray)?;
+        let dir = ray.dir - normal * (2.0 * ray.dir.dot(normal));
+        Some(Ray { origin: ray.at(t), dir })
+    }
+
+    // Moves a point by `vel` and bounces it off the walls the way the engine bounces the ball:
+    // put back inside, the velocity component towards the wall flipped
+    pub fn bounce(&self, pos: Vec2, vel: Vec2) -> (Vec2, Vec2) {
+        let (mut pos, mut vel) = (pos + vel, vel);
+        if pos.x < self.min.x {
+            pos.x = self.min.x;
+            vel.x = vel.x.abs();
+        }
+        if pos.x > self.max.x {
+            pos.x = self.max.x;
+            vel.x = -vel.x.abs();
+        }
+        if pos.y < self.min.y {
+            pos.y = self.min.y;
+            vel.y = vel.y.abs();
+        }
+        if pos.y > self.max.y {
+            pos.y = self.max.y;
+            vel.y = -vel.y.abs();
+        }
+        (pos, vel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        a.approx_eq(&b, 1e-4)
+    }
+
+    fn ray(origin: Vec2, dir: Vec2) -> Ray {
+        Ray::new(origin, dir).unwrap()
+    }
+
+    #[test]
+    fn segments_cross() {
+        let a = Segment::new(Vec2::new(0.0, 0.0), Vec2::new(10.0, 10.0));
+        let b = Segment::new(Vec2::new(0.0, 10.0), Vec2::new(10.0, 0.0));
+        assert!(close(a.intersect(&b).unwrap(), Vec2::new(5.0, 5.0)));
+        assert!(close(b.intersect(&a).unwrap(), Vec2::new(5.0, 5.0)));
+
+        // touching at an end point counts
+        let c = Segment::new(Vec2::new(10.0, 10.0), Vec2::new(20.0, 0.0));
+        assert!(close(a.intersect(&c).unwrap(), Vec2::new(10.0, 10.0)));
+    }
+
+    #[test]
+    fn segments_miss() {
+        // the lines cross at (5, 5), past the end of the first segment
+        let short = Segment::new(Vec2::new(0.0, 0.0), Vec2::new(4.0, 4.0));
+        let b = Segment::new(Vec2::new(0.0, 10.0), Vec2::new(10.0, 0.0));
+        assert_eq!(short.intersect(&b), None);
+
+        let parallel = Segment::new(Vec2::new(0.0, 5.0), Vec2::new(10.0, 5.0));
+        let below = Segment::new(Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0));
+        assert_eq!(parallel.intersect(&below), None);
+
+        // overlapping on the same line is parallel too
+        let overlap = Segment::new(Vec2::new(5.0, 0.0), Vec2::new(15.0, 0.0));
+        assert_eq!(below.intersect(&overlap), None);
+    }
+
+    #[test]
+    fn ray_enters_circle() {
+        let r = ray(Vec2::ZERO, Vec2::new(3.0, 0.0));
+        let hit = r.intersect_circle(&Circle::new(Vec2::new(10.0, 0.0), 2.0)).unwrap();
+        assert!((hit - 8.0).abs() < 1e-4);
+
+        // grazing the top of the circle
+        let tangent = r.intersect_circle(&Circle::new(Vec2::new(10.0, 2.0), 2.0)).unwrap();
+        assert!((tangent - 10.0).abs() < 1e-3);
+
+        // passing 3 off the center of a radius 5 circle, it enters 4 before the center
+        let diagonal = ray(Vec2::new(0.0, 3.0), Vec2::new(1.0, 0.0));
+        let hit = diagonal.intersect_circle(&Circle::new(Vec2::new(8.0, 0.0), 5.0)).unwrap();
+        assert!((hit - 4.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn ray_misses_circle() {
+        let r = ray(Vec2::ZERO, Vec2::new(1.0, 0.0));
+        assert_eq!(r.intersect_circle(&Circle::new(Vec2::new(10.0, 5.0), 2.0)), None);
+        // behind the origin
+        assert_eq!(r.intersect_circle(&Circle::new(Vec2::new(-10.0, 0.0), 2.0)), None);
+    }
+
+    #[test]
+    fn ray_starting_in_circle() {
+        let r = ray(Vec2::ZERO, Vec2::new(-1.0, 0.0));
+        assert_eq!(r.intersect_circle(&Circle::new(Vec2::new(1.0, 0.0), 2.0)), Some(0.0));
+    }
+
+    #[test]
+    fn rect_exit() {
+        let rect = Rect::new(Vec2::ZERO, Vec2::new(10.0, 20.0));
+
+        let (t, normal) = rect.exit(&ray(Vec2::new(5.0, 5.0), Vec2::new(1.0, 0.0))).unwrap();
+        assert!((t - 5.0).abs() < 1e-4);
+        assert_eq!(normal, Vec2::new(-1.0, 0.0));
+
+        let (t, normal) = rect.exit(&ray(Vec2::new(5.0, 5.0), Vec2::new(0.0, -1.0))).unwrap();
+        assert!((t - 5.0).abs() < 1e-4);
+        assert_eq!(normal, Vec2::new(0.0, 1.0));
+
+        // diagonal, the right wall comes before the bottom one
+        let (t, normal) = rect.exit(&ray(Vec2::new(5.0, 10.0), Vec2::new(1.0, 1.0))).unwrap();
+        assert!((t - 5.0 * 2f32.sqrt()).abs() < 1e-4);
+        assert_eq!(normal, Vec2::new(-1.0, 0.0));
+
+        // outside and heading away, every wall is behind
+        assert_eq!(rect.exit(&ray(Vec2::new(-5.0, 5.0), Vec2::new(-1.0, 0.0))), None);
+    }
+
+    #[test]
+    fn rect_reflect() {
+        let rect = Rect::new(Vec2::ZERO, Vec2::new(10.0, 20.0));
+
+        let back = rect.reflect(&ray(Vec2::new(5.0, 5.0), Vec2::new(1.0, 0.0))).unwrap();
+        assert!(close(back.origin, Vec2::new(10.0, 5.0)));
+        assert!(close(back.dir, Vec2::new(-1.0, 0.0)));
+
+        let off = rect.reflect(&ray(Vec2::new(5.0, 10.0), Vec2::new(1.0, 1.0))).unwrap();
+        assert!(close(off.origin, Vec2::new(10.0, 15.0)));
+        assert!(close(off.dir, Vec2::new(-1.0, 1.0).normalize_or_zero()));
+    }
+
+    #[test]
+    fn rect_bounce() {
+        let rect = Rect::new(Vec2::ZERO, Vec2::new(10.0, 10.0));
+
+        // staying inside is a plain move
+        assert_eq!(rect.bounce(Vec2::new(5.0, 5.0), Vec2::new(1.0, 1.0)), (Vec2::new(6.0, 6.0), Vec2::new(1.0, 1.0)));
+
+        // through the right wall: put back on it, x velocity flipped
+        assert_eq!(rect.bounce(Vec2::new(9.0, 5.0), Vec2::new(3.0, 1.0)), (Vec2::new(10.0, 6.0), Vec2::new(-3.0, 1.0)));
+
+        // through a corner: both flipped
+        assert_eq!(rect.bounce(Vec2::new(1.0, 1.0), Vec2::new(-3.0, -3.0)), (Vec2::ZERO, Vec2::new(3.0, 3.0)));
+
+        // already heading back in, the velocity is kept
+        assert_eq!(rect.bounce(Vec2::new(-1.0, 5.0), Vec2::new(0.5, 0.0)), (Vec2::new(0.0, 5.0), Vec2::new(0.5, 0.0)));
+    }
+}
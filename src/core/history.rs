@@ -0,0 +1,156 @@
+#![allow(dead_code)]
+
+// The last few states handed to `on_tick`, with the quantities only a sequence of them tells:
+// how fast players and the ball actually move, and how long ago the ball changed hands or the
+// field was reset. Kept by whoever drives the strategy (the engine channel, or `sim::Match`).
+
+use std::collections::VecDeque;
+use super::state::*;
+use super::util::*;
+
+pub const DEFAULT_HISTORY_LEN: usize = 32;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct StateHistory {
+    capacity: usize,
+    // oldest first
+    states: VecDeque<GameState>,
+    // tick of the first state after the last reset
+    reset_tick: Option<u32>,
+    pending_reset: bool,
+    possession_changed_at: Option<u32>,
+}
+
+// Possession without the capture counter, which changes every tick
+fn possession_key(possession: &BallPossessionState) -> (u8, Option<PlayerId>, Option<Team>) {
+    match *possession {
+        BallPossessionState::Possessed { owner, team, .. } => (0, Some(owner), Some(team)),
+        BallPossessionState::Passing { team } => (1, None, Some(team)),
+        BallPossessionState::Free => (2, None, None),
+    }
+}
+
+impl StateHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            states: VecDeque::with_capacity(capacity.max(1)),
+            reset_tick: None,
+            pending_reset: false,
+            possession_changed_at: None,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new(self.capacity);
+    }
+
+    // The field was reset, the next state pushed is a kickoff
+    pub fn mark_reset(&mut self) {
+        self.pending_reset = true;
+    }
+
+    pub fn push(&mut self, state: &GameState) {
+        if let Some(last) = self.states.back() {
+            // a tick going backwards means a new match
+            if state.tick < last.tick {
+                self.clear();
+            } else if possession_key(&state.ball_possession) != possession_key(&last.ball_possession) {
+                self.possession_changed_at = Some(state.tick);
+            }
+        }
+        if self.pending_reset || self.states.is_empty() {
+            self.reset_tick = Some(state.tick);
+            self.pending_reset = false;
+        }
+        if self.states.len() == self.capacity {
+            self.states.pop_front();
+        }
+        self.states.push_back(state.clone());
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    // `ago` ticks back, 0 being the current state
+    pub fn get(&self, ago: usize) -> Option<&GameState> {
+        self.states.len().checked_sub(ago + 1).map(|i| &self.states[i])
+    }
+
+    pub fn current(&self) -> Option<&GameState> {
+        self.get(0)
+    }
+
+    pub fn previous(&self) -> Option<&GameState> {
+        self.get(1)
+    }
+
+    // Newest first
+    pub fn iter(&self) -> impl Iterator<Item = &GameState> {
+        self.states.iter().rev()
+    }
+
+    // Like `get`, but never reaches back past the last reset, where positions jump
+    fn since_reset(&self, ago: usize) -> Option<&GameState> {
+        self.get(ago).filter(|state| self.reset_tick.is_some_and(|tick| state.tick >= tick))
+    }
+
+    fn displacement(&self, ago: usize, pos: impl Fn(&GameState) -> Vec2) -> Option<Vec2> {
+        let (after, before) = (self.since_reset(ago)?, self.since_reset(ago + 1)?);
+        let ticks = after.tick.saturating_sub(before.tick).max(1) as f32;
+        Some((pos(after) - pos(before)) / ticks)
+    }
+
+    // Distance per tick the player actually moved over the last tick, unlike `dir` which is
+    // only what was asked for
+    pub fn player_velocity(&self, id: PlayerId) -> Option<Vec2> {
+        self.displacement(0, |state| state.players[id as usize].pos)
+    }
+
+    pub fn player_acceleration(&self, id: PlayerId) -> Option<Vec2> {
+        let now = self.displacement(0, |state| state.players[id as usize].pos)?;
+        let before = self.displacement(1, |state| state.players[id as usize].pos)?;
+        Some(now - before)
+    }
+
+    pub fn ball_velocity(&self) -> Option<Vec2> {
+        self.displacement(0, |state| state.ball.pos)
+    }
+
+    // How far the observed ball movement is from what `BallState.vel` predicted: the previous
+    // velocity for a rolling ball, the current one for a carried ball (it is set to the
+    // carrier's step). Large values mean a bounce, a pass or a kick happened in between.
+    pub fn ball_velocity_error(&self) -> Option<f32> {
+        let observed = self.ball_velocity()?;
+        let (current, previous) = (self.current()?, self.previous()?);
+        let expected = match current.ball_possession {
+            BallPossessionState::Possessed { .. } => current.ball.vel,
+            _ => previous.ball.vel,
+        };
+        Some(observed.dist(&expected))
+    }
+
+    // `None` until the ball changed hands at least once
+    pub fn ticks_since_possession_change(&self) -> Option<u32> {
+        Some(self.current()?.tick.saturating_sub(self.possession_changed_at?))
+    }
+
+    pub fn ticks_since_reset(&self) -> Option<u32> {
+        Some(self.current()?.tick.saturating_sub(self.reset_tick?))
+    }
+}
+
+impl Default for StateHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_LEN)
+    }
+}
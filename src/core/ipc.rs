@@ -11,11 +11,13 @@ use std::{
     sync::atomic::{AtomicU8, Ordering},
     time::Duration,
 };
+use std::cell::RefCell;
 use std::sync::OnceLock;
 use crate::core::{
     util::Vec2,
     config::{ GameConfig, NUM_PLAYERS },
     state::{ Team, GameState, PlayerAction, TeamPair },
+    history::StateHistory,
 };
 
 #[repr(u8)]
@@ -90,7 +92,8 @@ async fn poll(au8: &AtomicU8, cmp: u8) {
 
 pub struct Strategy {
     pub on_reset: Box<dyn Fn(&TeamPair<u32>) -> [Vec2; NUM_PLAYERS as usize]>,
-    pub on_tick: Box<dyn Fn(&GameState) -> [PlayerAction; NUM_PLAYERS as usize]>,
+    // the history already contains the state
+    pub on_tick: Box<dyn Fn(&GameState, &StateHistory) -> [PlayerAction; NUM_PLAYERS as usize]>,
 }
 
 // safe because we only grab one byte
@@ -106,8 +109,22 @@ pub fn get_config() -> &'static GameConfig {
     CONFIG.get().unwrap()
 }
 
+// Sets the config outside of a handshake, for offline tools that run strategies in-process
+pub fn init_config(config: GameConfig) -> anyhow::Result<()> {
+    CONFIG.set(config).map_err(|_| anyhow::anyhow!("game config was already initialized"))
+}
+
+pub fn load_config<P: AsRef<Path>>(path: P) -> anyhow::Result<GameConfig> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("unable to read game config {}", path.display()))?;
+    serde_json::from_str(&text)
+        .with_context(|| format!("unable to parse game config {}", path.display()))
+}
+
 pub struct EngineChannel {
     mmap: MmapMut,
+    history: RefCell<StateHistory>,
 }
 
 impl EngineChannel {
@@ -122,6 +139,7 @@ impl EngineChannel {
             mmap: unsafe {
                 MmapMut::map_mut(&file).with_context(|| "unable to memory map backing file")?
             },
+            history: RefCell::new(StateHistory::default()),
         })
     }
 
@@ -164,8 +182,15 @@ impl EngineChannel {
         let msg = unsafe { &mut* (self.mmap.as_ptr().add(offset_of!(Shm, protocol)) as *mut ProtocolUnion) };
 
         let response = match msg {
-            ProtocolUnion::ResetMsg(score) => ProtocolUnion::ResetResponse((strategy.on_reset)(score)),
-            ProtocolUnion::TickMsg(state) => ProtocolUnion::TickResponse((strategy.on_tick)(state)),
+            ProtocolUnion::ResetMsg(score) => {
+                self.history.borrow_mut().mark_reset();
+                ProtocolUnion::ResetResponse((strategy.on_reset)(score))
+            },
+            ProtocolUnion::TickMsg(state) => {
+                let mut history = self.history.borrow_mut();
+                history.push(state);
+                ProtocolUnion::TickResponse((strategy.on_tick)(state, &history))
+            },
             _ => panic!()
         };
 
@@ -4,12 +4,22 @@
 pub mod config;
 pub mod state;
 pub mod util;
+pub mod geometry;
+pub mod batch;
 pub mod ipc;
+pub mod sim;
+pub mod history;
+pub mod events;
 
 pub use config::*;
 pub use state::*;
 pub use util::*;
+pub use geometry::*;
+pub use batch::{ PlayerBatch, PlayerMask };
+pub use history::StateHistory;
 pub use ipc::{
     get_config,
+    init_config,
+    load_config,
     Strategy,
 };
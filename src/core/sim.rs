@@ -0,0 +1,437 @@
+#![allow(dead_code)]
+
+// An offline approximation of the engine, used to play matches in-process.
+//
+// The world is always stored from the point of view of the team on the left (`Team::Me`),
+// exactly like the state your strategy receives. The rules follow the engine as closely as
+// the config allows:
+//   - players move `speed` per tick along their (normalized) direction, slowed by
+//     `possession_slowdown` while carrying the ball, and are pushed apart on overlap
+//   - a free or passed ball travels by its velocity and loses `friction` of it every tick
+//   - a pass leaves at `pass_speed`, off by up to `pass_error` degrees
+//   - `capture_ticks` ticks after gaining the ball, a carrier loses it to any opponent in reach
+//   - a ball that stays within `stagnation_radius` for `stagnation_ticks` ticks resets the field
+//   - the field resets right before endgame and the match ends `endgame_ticks` ticks later
+
+use rand::{ Rng, SeedableRng, rngs::StdRng };
+use super::config::*;
+use super::state::*;
+use super::util::*;
+use super::geometry::Segment;
+use super::ipc::Strategy;
+use super::history::StateHistory;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StepEvent {
+    None,
+    Goal(Team),
+    Stagnation,
+    Endgame,
+    Finished,
+}
+
+impl StepEvent {
+    // whether the field has to be reset before the next step
+    pub fn needs_reset(&self) -> bool {
+        matches!(self, StepEvent::Goal(_) | StepEvent::Stagnation | StepEvent::Endgame)
+    }
+}
+
+#[derive(Clone)]
+pub struct Simulator<'a> {
+    conf: &'a GameConfig,
+    state: GameState,
+    rng: StdRng,
+    passer: Option<PlayerId>,
+}
+
+impl<'a> Simulator<'a> {
+    pub fn new(conf: &'a GameConfig, seed: u64) -> Self {
+        Self::from_state(conf, GameState::new(conf), seed)
+    }
+
+    // Continues a game from an arbitrary state, e.g. the one handed to `on_tick`
+    pub fn from_state(conf: &'a GameConfig, state: GameState, seed: u64) -> Self {
+        Self {
+            conf,
+            state,
+            rng: StdRng::seed_from_u64(seed),
+            passer: None,
+        }
+    }
+
+    pub fn conf(&self) -> &'a GameConfig {
+        self.conf
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state.tick >= self.conf.max_ticks + self.conf.endgame_ticks
+    }
+
+    // Places both teams and the ball for a kickoff, positions are in world coordinates
+    pub fn reset(&mut self, positions: &TeamPair<[Vec2; NUM_PLAYERS as usize]>) {
+        let conf = self.conf;
+        let center = conf.field.center();
+
+        self.state.ball = BallState {
+            pos: center,
+            vel: Vec2::ZERO,
+            radius: conf.ball.radius,
+        };
+        self.state.ball_possession = BallPossessionState::Free;
+        self.state.ball_stagnation = BallStagnationState {
+            center,
+            tick: self.state.tick,
+        };
+        self.passer = None;
+
+        for team in [Team::Me, Team::Other] {
+            for (player, &pos) in self.state.players[team].iter_mut().zip(positions[team].iter()) {
+                player.pos = spawn_pos(pos, team, conf);
+                player.dir = Vec2::ZERO;
+            }
+        }
+    }
+
+    // Advances the game by one tick, actions are in world coordinates
+    pub fn step(&mut self, actions: &TeamPair<TeamAction>) -> StepEvent {
+        if self.is_finished() {
+            return StepEvent::Finished;
+        }
+        let conf = self.conf;
+        self.state.tick += 1;
+
+        let action = |id: usize| -> &PlayerAction {
+            if id < NUM_PLAYERS as usize {
+                &actions.me[id]
+            } else {
+                &actions.other[id - NUM_PLAYERS as usize]
+            }
+        };
+
+        // the carrier releases the ball before anyone moves
+        if let BallPossessionState::Possessed { owner, team, .. } = self.state.ball_possession {
+            if let StateOption::Some(dir) = action(owner as usize).pass {
+                if dir.norm_sq() > 0.0 {
+                    let error = self.rng.random_range(-1.0..=1.0) * conf.player.pass_error;
+                    self.state.ball.vel = dir.normalize_or_zero().rotate_deg(error) * conf.player.pass_speed;
+                    self.state.ball_possession = BallPossessionState::Passing { team };
+                    self.passer = Some(owner);
+                }
+            }
+        }
+
+        let owner = self.state.ball_owner();
+        for (id, player) in self.state.players.iter_mut().enumerate() {
+            let dir = action(id).dir.normalize_or_zero();
+            let speed = if owner == Some(id as u32) {
+                player.speed * conf.player.possession_slowdown
+            } else {
+                player.speed
+            };
+            player.dir = dir;
+            player.pos += dir * speed;
+            clamp_to_field(&mut player.pos, player.radius, conf);
+        }
+        resolve_collisions(&mut self.state.players, conf);
+
+        let event = self.move_ball();
+        if event != StepEvent::None {
+            return event;
+        }
+        self.update_possession();
+
+        if self.update_stagnation() {
+            return StepEvent::Stagnation;
+        }
+        if self.state.tick == conf.max_ticks {
+            return StepEvent::Endgame;
+        }
+        if self.is_finished() {
+            return StepEvent::Finished;
+        }
+        StepEvent::None
+    }
+
+    fn move_ball(&mut self) -> StepEvent {
+        let conf = self.conf;
+        let ball = &mut self.state.ball;
+
+        if let BallPossessionState::Possessed { owner, .. } = self.state.ball_possession {
+            let carrier = &self.state.players[owner as usize];
+            ball.vel = carrier.pos - ball.pos;
+            ball.pos = carrier.pos;
+            return StepEvent::None;
+        }
+
+        ball.pos += ball.vel;
+        ball.vel *= conf.ball.decay();
+
+        let height = conf.field.height as f32;
+        let width = conf.field.width as f32;
+        // both goals span the same y
+        let mouth = Segment::goal_mouth_at(conf, Team::Me, self.state.tick);
+        let in_mouth = (mouth.a.y..=mouth.b.y).contains(&ball.pos.y);
+
+        if in_mouth && ball.pos.x < 0.0 {
+            self.state.score.other += 1;
+            return StepEvent::Goal(Team::Other);
+        }
+        if in_mouth && ball.pos.x > width {
+            self.state.score.me += 1;
+            return StepEvent::Goal(Team::Me);
+        }
+
+        if !in_mouth && ball.pos.x < ball.radius {
+            ball.pos.x = ball.radius;
+            ball.vel.x = ball.vel.x.abs();
+        }
+        if !in_mouth && ball.pos.x > width - ball.radius {
+            ball.pos.x = width - ball.radius;
+            ball.vel.x = -ball.vel.x.abs();
+        }
+        if ball.pos.y < ball.radius {
+            ball.pos.y = ball.radius;
+            ball.vel.y = ball.vel.y.abs();
+        }
+        if ball.pos.y > height - ball.radius {
+            ball.pos.y = height - ball.radius;
+            ball.vel.y = -ball.vel.y.abs();
+        }
+        StepEvent::None
+    }
+
+    fn update_possession(&mut self) {
+        let conf = self.conf;
+        let ball = self.state.ball.pos;
+
+        let nearest_in_reach = |players: &[PlayerState], skip: Option<PlayerId>| {
+            players
+                .iter()
+                .filter(|p| Some(p.id) != skip && p.pos.dist(&ball) <= p.pickup_radius)
+                .min_by(|a, b| a.pos.dist_sq(&ball).total_cmp(&b.pos.dist_sq(&ball)))
+                .map(|p| p.id)
+        };
+
+        match self.state.ball_possession {
+            BallPossessionState::Possessed { owner, team, capture_ticks } => {
+                let thief = nearest_in_reach(&self.state.players[team.other()], None);
+                self.state.ball_possession = match thief {
+                    Some(thief) if capture_ticks >= conf.ball.capture_ticks => BallPossessionState::Possessed {
+                        owner: thief,
+                        team: team.other(),
+                        capture_ticks: 0,
+                    },
+                    _ => BallPossessionState::Possessed {
+                        owner,
+                        team,
+                        capture_ticks: capture_ticks + 1,
+                    },
+                };
+            },
+            BallPossessionState::Passing { .. } | BallPossessionState::Free => {
+                // the passer can only pick the ball back up once it has left their reach
+                if let Some(passer) = self.passer {
+                    let p = &self.state.players[passer as usize];
+                    if p.pos.dist(&ball) > p.pickup_radius {
+                        self.passer = None;
+                    }
+                }
+                if let Some(id) = nearest_in_reach(&self.state.players, self.passer) {
+                    self.state.ball_possession = BallPossessionState::Possessed {
+                        owner: id,
+                        team: self.state.player_team(id).unwrap(),
+                        capture_ticks: 0,
+                    };
+                    self.state.ball.vel = Vec2::ZERO;
+                    self.passer = None;
+                } else if self.state.ball.vel.norm() < EPSILON {
+                    self.state.ball_possession = BallPossessionState::Free;
+                }
+            },
+        }
+    }
+
+    fn update_stagnation(&mut self) -> bool {
+        let conf = self.conf;
+        let stagnation = &mut self.state.ball_stagnation;
+        if self.state.ball.pos.dist(&stagnation.center) > conf.ball.stagnation_radius {
+            stagnation.center = self.state.ball.pos;
+            stagnation.tick = self.state.tick;
+            return false;
+        }
+        self.state.tick - stagnation.tick >= conf.ball.stagnation_ticks
+    }
+}
+
+fn clamp_to_field(pos: &mut Vec2, radius: f32, conf: &GameConfig) {
+    let field = conf.field.bottom_right();
+    pos.x = pos.x.clamp(radius, (field.x - radius).max(radius));
+    pos.y = pos.y.clamp(radius, (field.y - radius).max(radius));
+}
+
+// Keeps kickoff positions on the team's own half and outside of `spawn_ball_dist`
+fn spawn_pos(mut pos: Vec2, team: Team, conf: &GameConfig) -> Vec2 {
+    let radius = conf.player.radius;
+    let center = conf.field.center();
+
+    clamp_to_field(&mut pos, radius, conf);
+    pos.x = match team {
+        Team::Me => pos.x.min(center.x - radius),
+        Team::Other => pos.x.max(center.x + radius),
+    };
+
+    if pos.dist(&center) < conf.spawn_ball_dist {
+        let away = match team {
+            Team::Me => Vec2::new(-1.0, 0.0),
+            Team::Other => Vec2::new(1.0, 0.0),
+        };
+        let dir = (pos - center).normalize_or_else(|| away);
+        pos = center + dir * conf.spawn_ball_dist;
+    }
+    pos
+}
+
+fn resolve_collisions(players: &mut PlayerArray<PlayerState>, conf: &GameConfig) {
+    for _ in 0..COLLISION_MAX_ITERATIONS {
+        let mut moved = false;
+        for i in 0..players.len() {
+            for j in (i + 1)..players.len() {
+                let delta = players[j].pos - players[i].pos;
+                let min_dist = players[i].radius + players[j].radius;
+                let dist = delta.norm();
+                if dist >= min_dist - EPSILON {
+                    continue;
+                }
+                let push = delta.normalize_or_else(|| Vec2::new(1.0, 0.0)) * ((min_dist - dist) * 0.5);
+                players[i].pos -= push;
+                players[j].pos += push;
+                let (ri, rj) = (players[i].radius, players[j].radius);
+                clamp_to_field(&mut players[i].pos, ri, conf);
+                clamp_to_field(&mut players[j].pos, rj, conf);
+                moved = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+}
+
+// Returns the state as the given team sees it
+pub fn perspective(state: &GameState, team: Team, conf: &GameConfig) -> GameState {
+    let mut view = state.clone();
+    if team == Team::Other {
+        view.mirror(conf);
+    }
+    view
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MatchResult {
+    pub score: TeamPair<u32>,
+    pub ticks: u32,
+}
+
+impl MatchResult {
+    pub fn goal_diff(&self) -> i32 {
+        self.score.me as i32 - self.score.other as i32
+    }
+
+    // 1 for a win, 0.5 for a draw and 0 for a loss, from `Team::Me`'s point of view
+    pub fn outcome(&self) -> f32 {
+        match self.score.me.cmp(&self.score.other) {
+            std::cmp::Ordering::Greater => 1.0,
+            std::cmp::Ordering::Equal => 0.5,
+            std::cmp::Ordering::Less => 0.0,
+        }
+    }
+}
+
+// A full match between two strategies, each seeing itself on the left
+pub struct Match<'a> {
+    sim: Simulator<'a>,
+    strategies: TeamPair<&'a Strategy>,
+    // what each strategy has seen, from its own point of view
+    histories: TeamPair<StateHistory>,
+}
+
+impl<'a> Match<'a> {
+    pub fn new(conf: &'a GameConfig, me: &'a Strategy, other: &'a Strategy, seed: u64) -> Self {
+        let mut game = Self {
+            sim: Simulator::new(conf, seed),
+            strategies: TeamPair::new(me, other),
+            histories: TeamPair::new(StateHistory::default(), StateHistory::default()),
+        };
+        game.reset();
+        game
+    }
+
+    pub fn sim(&self) -> &Simulator<'a> {
+        &self.sim
+    }
+
+    pub fn state(&self) -> &GameState {
+        self.sim.state()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.sim.is_finished()
+    }
+
+    fn reset(&mut self) {
+        let conf = self.sim.conf();
+        let score = self.sim.state().score;
+
+        let me = (self.strategies.me.on_reset)(&score);
+        let mut other_score = score;
+        other_score.mirror(conf);
+        let mut other = (self.strategies.other.on_reset)(&other_score);
+        other.iter_mut().for_each(|pos| mirror_pos(pos, conf));
+
+        self.sim.reset(&TeamPair::new(me, other));
+        self.histories.iter_mut().for_each(|history| history.mark_reset());
+    }
+
+    pub fn history(&self, team: Team) -> &StateHistory {
+        &self.histories[team]
+    }
+
+    pub fn step(&mut self) -> StepEvent {
+        let conf = self.sim.conf();
+        let views = TeamPair::new(self.sim.state().clone(), perspective(self.sim.state(), Team::Other, conf));
+        self.histories.me.push(&views.me);
+        self.histories.other.push(&views.other);
+        let me = (self.strategies.me.on_tick)(&views.me, &self.histories.me);
+        let mut other = (self.strategies.other.on_tick)(&views.other, &self.histories.other);
+        other.mirror(conf);
+
+        let event = self.sim.step(&TeamPair::new(me, other));
+        if event.needs_reset() {
+            self.reset();
+        }
+        event
+    }
+
+    pub fn play(mut self) -> MatchResult {
+        while !self.is_finished() {
+            self.step();
+        }
+        self.result()
+    }
+
+    pub fn result(&self) -> MatchResult {
+        MatchResult {
+            score: self.state().score,
+            ticks: self.state().tick,
+        }
+    }
+}
+
+pub fn play_match(conf: &GameConfig, me: &Strategy, other: &Strategy, seed: u64) -> MatchResult {
+    Match::new(conf, me, other, seed).play()
+}
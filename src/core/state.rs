@@ -5,7 +5,7 @@ use super::util::Vec2;
 use super::config::*;
 use std::ops::{ Index, IndexMut };
 
-type PlayerId = u32;
+pub type PlayerId = u32;
 
 #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
 #[repr(u8, C)]
@@ -1,8 +1,10 @@
 #![allow(dead_code)]
 #![allow(unused_imports)]
 
+use rand::Rng;
 use serde::{ Serialize, Deserialize };
 use std::ops::{ Add, AddAssign, Sub, SubAssign, Mul, MulAssign, Div, DivAssign, Neg };
+use super::config::EPSILON;
 
 pub use std::f32::consts::PI;
 
@@ -102,6 +104,97 @@ impl Vec2 {
         (*other - *self).norm()
     }
 
+    // Rotated a quarter turn, from +x towards +y
+    #[inline(always)]
+    pub fn perp(self) -> Self {
+        Vec2 { x: -self.y, y: self.x }
+    }
+
+    // z of the 3D cross product, positive when `other` is on the `perp` side of `self`
+    #[inline(always)]
+    pub fn cross(self, other: Vec2) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+
+    // Part of `self` along `other`, zero when `other` is zero
+    pub fn project_onto(self, other: Vec2) -> Self {
+        let norm_sq = other.norm_sq();
+        if norm_sq == 0.0 {
+            return Vec2::ZERO;
+        }
+        other * (self.dot(other) / norm_sq)
+    }
+
+    // Part of `self` perpendicular to `other`
+    #[inline(always)]
+    pub fn reject_from(self, other: Vec2) -> Self {
+        self - self.project_onto(other)
+    }
+
+    // `self` at 0, `other` at 1
+    #[inline(always)]
+    pub fn lerp(self, other: Vec2, t: f32) -> Self {
+        self + (other - self) * t
+    }
+
+    pub fn clamp_length(self, max: f32) -> Self {
+        let norm = self.norm();
+        if norm <= max {
+            return self;
+        }
+        self * (max / norm)
+    }
+
+    // Same direction, zero stays zero
+    #[inline(always)]
+    pub fn with_length(self, length: f32) -> Self {
+        self.normalize_or_zero() * length
+    }
+
+    // Unsigned, in radians between 0 and PI, zero when either is zero
+    pub fn angle_between(self, other: Vec2) -> f32 {
+        self.signed_angle(other).abs()
+    }
+
+    // Radians to rotate `self` by to point along `other`, between -PI and PI
+    #[inline(always)]
+    pub fn signed_angle(self, other: Vec2) -> f32 {
+        self.cross(other).atan2(self.dot(other))
+    }
+
+    // Equal up to `eps` on each axis
+    #[inline(always)]
+    pub fn approx_eq(&self, other: &Vec2, eps: f32) -> bool {
+        (self.x - other.x).abs() <= eps && (self.y - other.y).abs() <= eps
+    }
+
+    // `approx_eq` up to `EPSILON`
+    #[inline(always)]
+    pub fn approx_eq_default(&self, other: &Vec2) -> bool {
+        self.approx_eq(other, EPSILON)
+    }
+
+    #[inline(always)]
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite()
+    }
+
+    // Component-wise
+    #[inline(always)]
+    pub fn min(self, other: Vec2) -> Self {
+        Vec2 { x: self.x.min(other.x), y: self.y.min(other.y) }
+    }
+
+    #[inline(always)]
+    pub fn max(self, other: Vec2) -> Self {
+        Vec2 { x: self.x.max(other.x), y: self.y.max(other.y) }
+    }
+
+    #[inline(always)]
+    pub fn abs(self) -> Self {
+        Vec2 { x: self.x.abs(), y: self.y.abs() }
+    }
+
 }
 
 impl Add<Vec2> for Vec2 {
@@ -147,6 +240,17 @@ impl Mul<f32> for Vec2 {
     }
 }
 
+// Component-wise
+impl Mul<Vec2> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(mut self, other: Vec2) -> Vec2 {
+        self.x *= other.x;
+        self.y *= other.y;
+        self
+    }
+}
+
 impl Mul<Vec2> for f32 {
     type Output = Vec2;
 
@@ -209,3 +313,114 @@ impl<'a> std::iter::Sum<&'a Vec2> for Vec2 {
         iter.fold(Vec2 { x: 0.0, y: 0.0 }, |acc, v| acc + *v)
     }
 }
+
+// Standard normal sample (Box-Muller), `rand` alone does not ship a normal distribution
+pub fn sample_gaussian<R: Rng + ?Sized>(rng: &mut R) -> f32 {
+    let u1: f32 = rng.random_range(f32::EPSILON..1.0);
+    let u2: f32 = rng.random();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{ SeedableRng, rngs::StdRng };
+
+    const CASES: usize = 1000;
+
+    // field-sized vectors, with the odd zero and axis aligned one thrown in
+    fn vectors(seed: u64) -> impl Iterator<Item = Vec2> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..CASES).map(move |i| match i % 50 {
+            0 => Vec2::ZERO,
+            1 => Vec2::new(rng.random_range(-1000.0..1000.0), 0.0),
+            2 => Vec2::new(0.0, rng.random_range(-1000.0..1000.0)),
+            _ => Vec2::new(rng.random_range(-1000.0..1000.0), rng.random_range(-1000.0..1000.0)),
+        })
+    }
+
+    fn pairs(seed: u64) -> impl Iterator<Item = (Vec2, Vec2)> {
+        vectors(seed).zip(vectors(seed.wrapping_add(1)))
+    }
+
+    // rounding error grows with the magnitude of the operands
+    fn tolerance(vecs: &[Vec2]) -> f32 {
+        1e-4 * (1.0 + vecs.iter().map(|v| v.norm()).fold(0.0, f32::max))
+    }
+
+    #[test]
+    fn projection_and_rejection_add_up() {
+        for (a, b) in pairs(1) {
+            let sum = a.project_onto(b) + a.reject_from(b);
+            assert!(sum.approx_eq(&a, tolerance(&[a, b])), "{a:?} onto {b:?} gives {sum:?}");
+        }
+    }
+
+    #[test]
+    fn perp_is_orthogonal() {
+        for a in vectors(2) {
+            let dot = a.perp().dot(a);
+            assert!(dot.abs() <= tolerance(&[a]) * a.norm(), "{a:?}: {dot}");
+        }
+    }
+
+    #[test]
+    fn lerp_hits_endpoints() {
+        for (a, b) in pairs(3) {
+            let eps = tolerance(&[a, b]);
+            assert!(a.lerp(b, 0.0).approx_eq(&a, eps), "{a:?} {b:?} at 0");
+            assert!(a.lerp(b, 1.0).approx_eq(&b, eps), "{a:?} {b:?} at 1");
+        }
+    }
+
+    #[test]
+    fn rotating_by_signed_angle_aligns() {
+        for (a, b) in pairs(4) {
+            if a.norm() < 1.0 || b.norm() < 1.0 {
+                continue;
+            }
+            let rotated = a.rotate_rad(a.signed_angle(b)).normalize_or_zero();
+            let target = b.normalize_or_zero();
+            assert!(rotated.approx_eq(&target, 1e-4), "{a:?} to {b:?} gives {rotated:?}");
+        }
+    }
+
+    #[test]
+    fn clamp_length_stays_within_bound() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for a in vectors(6) {
+            let max = rng.random_range(0.0..1500.0);
+            let clamped = a.clamp_length(max);
+            assert!(clamped.norm() <= max * (1.0 + 1e-5), "{a:?} to {max} gives {clamped:?}");
+            if a.norm() <= max {
+                assert_eq!(clamped, a);
+            }
+        }
+    }
+
+    #[test]
+    fn approx_eq_is_symmetric() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for (a, b) in pairs(8) {
+            // mostly close pairs, so both outcomes show up
+            let b = if rng.random_bool(0.5) { a + (b - a) * 1e-3 } else { b };
+            let eps = rng.random_range(0.0..2.0);
+            assert_eq!(a.approx_eq(&b, eps), b.approx_eq(&a, eps), "{a:?} {b:?} {eps}");
+            assert!(a.approx_eq(&a, 0.0));
+        }
+        assert!(Vec2::ZERO.approx_eq(&Vec2::new(EPSILON, -EPSILON), EPSILON));
+        assert!(Vec2::ZERO.approx_eq_default(&Vec2::new(EPSILON, -EPSILON)));
+        assert!(!Vec2::ZERO.approx_eq_default(&Vec2::new(EPSILON * 2.0, 0.0)));
+    }
+
+    #[test]
+    fn min_max_abs_per_component() {
+        for (a, b) in pairs(9) {
+            let (min, max, abs) = (a.min(b), a.max(b), a.abs());
+            assert_eq!((min.x, min.y), (a.x.min(b.x), a.y.min(b.y)));
+            assert_eq!((max.x, max.y), (a.x.max(b.x), a.y.max(b.y)));
+            assert_eq!((abs.x, abs.y), (a.x.abs(), a.y.abs()));
+            assert!(min.x <= max.x && min.y <= max.y);
+        }
+    }
+}
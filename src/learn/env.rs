@@ -0,0 +1,235 @@
+#![allow(dead_code)]
+
+use anyhow::Context;
+use serde::{ Serialize, Deserialize };
+use std::fs::File;
+use std::io::{ BufWriter, Write };
+use std::path::Path;
+use crate::core::*;
+use crate::core::sim::{ Simulator, StepEvent, perspective };
+use super::features::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RewardConfig {
+    // per goal scored, minus per goal conceded
+    pub goal: f32,
+    // per field width the ball moves towards their goal
+    pub progress: f32,
+    // per tick with the ball in our possession
+    pub possession: f32,
+}
+
+impl Default for RewardConfig {
+    fn default() -> Self {
+        Self { goal: 1.0, progress: 0.0, possession: 0.0 }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Step {
+    pub obs: [f32; OBS_LEN],
+    pub reward: f32,
+    pub done: bool,
+    pub event: StepEvent,
+}
+
+// A full match against a fixed opponent, one `step` per tick. The agent plays `side`, but its
+// observations and actions are always from its own point of view, as in `on_tick`. `history`
+// is what the agent has seen so far, the current state included. The agent strategy places its
+// players at every kickoff, the actions are whatever is passed to `step`.
+pub struct Env<'a> {
+    conf: &'a GameConfig,
+    sim: Simulator<'a>,
+    side: Team,
+    agent: Strategy,
+    opponent: Strategy,
+    history: StateHistory,
+    opponent_history: StateHistory,
+    pub reward: RewardConfig,
+    seed: u64,
+    episode: u64,
+}
+
+impl<'a> Env<'a> {
+    pub fn new(conf: &'a GameConfig, side: Team, agent: Strategy, opponent: Strategy, seed: u64) -> Self {
+        Self {
+            conf,
+            sim: Simulator::new(conf, seed),
+            side,
+            agent,
+            opponent,
+            history: StateHistory::default(),
+            opponent_history: StateHistory::default(),
+            reward: RewardConfig::default(),
+            seed,
+            episode: 0,
+        }
+    }
+
+    pub fn side(&self) -> Team {
+        self.side
+    }
+
+    pub fn set_side(&mut self, side: Team) {
+        self.side = side;
+    }
+
+    // Swaps both teams, e.g. fresh ones before every episode so strategies with state start clean
+    pub fn set_strategies(&mut self, agent: Strategy, opponent: Strategy) {
+        self.agent = agent;
+        self.opponent = opponent;
+    }
+
+    pub fn episode(&self) -> u64 {
+        self.episode
+    }
+
+    // The state as the agent sees it
+    pub fn state(&self) -> GameState {
+        perspective(self.sim.state(), self.side, self.conf)
+    }
+
+    pub fn history(&self) -> &StateHistory {
+        &self.history
+    }
+
+    // What the agent strategy plays in the current state
+    pub fn agent_action(&self) -> TeamAction {
+        (self.agent.on_tick)(&self.state(), &self.history)
+    }
+
+    fn kickoff(&mut self) {
+        let conf = self.conf;
+        let score = self.sim.state().score;
+        let view = |team: Team| {
+            let mut score = score;
+            if team == Team::Other {
+                score.mirror(conf);
+            }
+            score
+        };
+
+        let agent = (self.agent.on_reset)(&view(self.side));
+        let opponent = (self.opponent.on_reset)(&view(self.side.other()));
+        let (me, mut other) = match self.side {
+            Team::Me => (agent, opponent),
+            Team::Other => (opponent, agent),
+        };
+        other.iter_mut().for_each(|pos| mirror_pos(pos, conf));
+        self.sim.reset(&TeamPair::new(me, other));
+        self.history.mark_reset();
+        self.opponent_history.mark_reset();
+    }
+
+    // Starts a new match, every episode gets its own seed derived from the one of the env
+    pub fn reset(&mut self) -> [f32; OBS_LEN] {
+        self.episode += 1;
+        self.sim = Simulator::new(self.conf, self.seed.wrapping_add(self.episode));
+        self.history.clear();
+        self.opponent_history.clear();
+        self.kickoff();
+        self.history.push(&self.state());
+        observe_as(self.sim.state(), self.side, self.conf)
+    }
+
+    pub fn step(&mut self, actions: &TeamAction) -> Step {
+        let conf = self.conf;
+        let before = self.state();
+
+        let opponent_view = perspective(self.sim.state(), self.side.other(), conf);
+        self.opponent_history.push(&opponent_view);
+        let mut opponent = (self.opponent.on_tick)(&opponent_view, &self.opponent_history);
+        let mut agent = actions.clone();
+        match self.side {
+            Team::Me => opponent.mirror(conf),
+            Team::Other => agent.mirror(conf),
+        }
+        let world = match self.side {
+            Team::Me => TeamPair::new(agent, opponent),
+            Team::Other => TeamPair::new(opponent, agent),
+        };
+
+        let event = self.sim.step(&world);
+        let after = self.state();
+        if event.needs_reset() {
+            self.kickoff();
+        }
+        self.history.push(&self.state());
+
+        let goals = (after.score.me as f32 - before.score.me as f32) - (after.score.other as f32 - before.score.other as f32);
+        let progress = if event.needs_reset() { 0.0 } else { (after.ball.pos.x - before.ball.pos.x) / conf.field.width as f32 };
+        let possession = match after.ball_possession {
+            BallPossessionState::Possessed { team: Team::Me, .. } => 1.0,
+            _ => 0.0,
+        };
+        let reward = goals * self.reward.goal + progress * self.reward.progress + possession * self.reward.possession;
+
+        Step {
+            obs: observe_as(self.sim.state(), self.side, conf),
+            reward,
+            done: self.sim.is_finished(),
+            event,
+        }
+    }
+}
+
+// One line of a rollout dump
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Transition {
+    pub episode: u64,
+    pub tick: u32,
+    pub obs: Vec<f32>,
+    pub action: Vec<f32>,
+    pub reward: f32,
+    pub done: bool,
+}
+
+// Writes transitions as json lines
+pub struct RolloutWriter {
+    out: BufWriter<File>,
+}
+
+impl RolloutWriter {
+    pub fn create<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .with_context(|| format!("unable to create rollout file {}", path.display()))?;
+        Ok(Self { out: BufWriter::new(file) })
+    }
+
+    pub fn write(&mut self, transition: &Transition) -> anyhow::Result<()> {
+        serde_json::to_writer(&mut self.out, transition)?;
+        self.out.write_all(b"\n")?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> anyhow::Result<()> {
+        self.out.flush().with_context(|| "unable to flush rollout file")
+    }
+}
+
+// Plays `episodes` matches with the agent strategy of `env` driving the actions and dumps every
+// transition
+pub fn record_rollouts(env: &mut Env, episodes: u64, writer: &mut RolloutWriter) -> anyhow::Result<()> {
+    for _ in 0..episodes {
+        let mut obs = env.reset();
+        loop {
+            let state = env.state();
+            let action = env.agent_action();
+            let step = env.step(&action);
+            writer.write(&Transition {
+                episode: env.episode(),
+                tick: state.tick,
+                obs: obs.to_vec(),
+                action: encode_action(&action).to_vec(),
+                reward: step.reward,
+                done: step.done,
+            })?;
+            obs = step.obs;
+            if step.done {
+                break;
+            }
+        }
+    }
+    writer.flush()
+}
@@ -0,0 +1,107 @@
+#![allow(dead_code)]
+
+// Fixed-length f32 encodings of states and actions. Everything is from `Team::Me`'s point of
+// view, mirror the state first (see `observe_as`) when playing the other side.
+//
+// Observation layout, `OBS_LEN` values:
+//   [0]       tick / max_ticks (goes past 1 during endgame)
+//   [1]       1 during endgame, 0 otherwise
+//   [2]       our score minus theirs
+//   [3..7]    ball pos.x / width, pos.y / height, vel.x / pass_speed, vel.y / pass_speed
+//   [7..12]   possession one-hot: free, we pass, they pass, we have it, they have it
+//   [12..20]  ball owner one-hot over all players, ours first
+//   [20..52]  per player, ours first: pos.x / width, pos.y / height, dir.x, dir.y
+//
+// Action layout, `ACTION_LEN` values per team, per player:
+//   dir.x, dir.y, pass flag (> 0.5 passes), pass.x, pass.y
+// with both directions encoded as unit vectors (or zero)
+
+use crate::core::*;
+
+const PLAYERS: usize = NUM_PLAYERS as usize * 2;
+
+pub const OBS_GLOBAL: usize = 3;
+pub const OBS_BALL: usize = 4;
+pub const OBS_POSSESSION: usize = 5;
+pub const OBS_OWNER: usize = PLAYERS;
+pub const OBS_PER_PLAYER: usize = 4;
+pub const OBS_LEN: usize = OBS_GLOBAL + OBS_BALL + OBS_POSSESSION + OBS_OWNER + OBS_PER_PLAYER * PLAYERS;
+
+pub const ACTION_PER_PLAYER: usize = 5;
+pub const ACTION_LEN: usize = ACTION_PER_PLAYER * NUM_PLAYERS as usize;
+
+pub fn observe(state: &GameState, conf: &GameConfig) -> [f32; OBS_LEN] {
+    let mut obs = [0.0; OBS_LEN];
+    let size = conf.field.bottom_right();
+    let pass_speed = conf.player.pass_speed.max(EPSILON);
+
+    obs[0] = state.tick as f32 / conf.max_ticks.max(1) as f32;
+    obs[1] = if state.tick > conf.max_ticks { 1.0 } else { 0.0 };
+    obs[2] = state.score.me as f32 - state.score.other as f32;
+
+    let ball = OBS_GLOBAL;
+    obs[ball] = state.ball.pos.x / size.x;
+    obs[ball + 1] = state.ball.pos.y / size.y;
+    obs[ball + 2] = state.ball.vel.x / pass_speed;
+    obs[ball + 3] = state.ball.vel.y / pass_speed;
+
+    let possession = ball + OBS_BALL;
+    let slot = match state.ball_possession {
+        BallPossessionState::Free => 0,
+        BallPossessionState::Passing { team: Team::Me } => 1,
+        BallPossessionState::Passing { team: Team::Other } => 2,
+        BallPossessionState::Possessed { team: Team::Me, .. } => 3,
+        BallPossessionState::Possessed { team: Team::Other, .. } => 4,
+    };
+    obs[possession + slot] = 1.0;
+
+    let owner = possession + OBS_POSSESSION;
+    if let Some(id) = state.ball_owner().filter(|&id| (id as usize) < PLAYERS) {
+        obs[owner + id as usize] = 1.0;
+    }
+
+    let players = owner + OBS_OWNER;
+    for (i, player) in state.players.iter().enumerate() {
+        let base = players + i * OBS_PER_PLAYER;
+        obs[base] = player.pos.x / size.x;
+        obs[base + 1] = player.pos.y / size.y;
+        obs[base + 2] = player.dir.x;
+        obs[base + 3] = player.dir.y;
+    }
+    obs
+}
+
+// Observation for `team`, mirroring the state when it is not the one on the left
+pub fn observe_as(state: &GameState, team: Team, conf: &GameConfig) -> [f32; OBS_LEN] {
+    match team {
+        Team::Me => observe(state, conf),
+        Team::Other => observe(&sim::perspective(state, team, conf), conf),
+    }
+}
+
+pub fn encode_action(action: &TeamAction) -> [f32; ACTION_LEN] {
+    let mut out = [0.0; ACTION_LEN];
+    for (i, player) in action.iter().enumerate() {
+        let base = i * ACTION_PER_PLAYER;
+        let dir = player.dir.normalize_or_zero();
+        out[base] = dir.x;
+        out[base + 1] = dir.y;
+        if let StateOption::Some(dir) = player.pass {
+            let dir = dir.normalize_or_zero();
+            out[base + 2] = 1.0;
+            out[base + 3] = dir.x;
+            out[base + 4] = dir.y;
+        }
+    }
+    out
+}
+
+pub fn decode_action(values: &[f32]) -> TeamAction {
+    std::array::from_fn(|i| {
+        let get = |j: usize| values.get(i * ACTION_PER_PLAYER + j).copied().unwrap_or(0.0);
+        PlayerAction {
+            dir: Vec2::new(get(0), get(1)),
+            pass: if get(2) > 0.5 { pass(Vec2::new(get(3), get(4))) } else { no_pass() },
+        }
+    })
+}
@@ -0,0 +1,284 @@
+#![allow(dead_code)]
+
+// CPU inference for small fully connected networks, so a policy trained offline on the data of
+// `bot rollouts` can play as a normal `Strategy`. Inputs and outputs follow `features`.
+//
+// Weights are read either from json (see `Mlp`) or from a little-endian binary file:
+//   b"MLP1", u32 layer count, then per layer
+//   u32 inputs, u32 outputs, u32 activation (0 linear, 1 relu, 2 tanh),
+//   outputs * inputs f32 weights (row per output), outputs f32 biases
+
+use anyhow::{ Context, bail, ensure };
+use serde::{ Serialize, Deserialize };
+use std::path::Path;
+use crate::core::*;
+use super::features::*;
+
+const MAGIC: &[u8; 4] = b"MLP1";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Activation {
+    #[default]
+    Linear,
+    Relu,
+    Tanh,
+}
+
+impl Activation {
+    fn apply(&self, x: f32) -> f32 {
+        match self {
+            Activation::Linear => x,
+            Activation::Relu => x.max(0.0),
+            Activation::Tanh => x.tanh(),
+        }
+    }
+
+    fn code(&self) -> u32 {
+        match self {
+            Activation::Linear => 0,
+            Activation::Relu => 1,
+            Activation::Tanh => 2,
+        }
+    }
+
+    fn from_code(code: u32) -> Option<Self> {
+        match code {
+            0 => Some(Activation::Linear),
+            1 => Some(Activation::Relu),
+            2 => Some(Activation::Tanh),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Layer {
+    pub inputs: usize,
+    pub outputs: usize,
+    // row-major, `outputs` rows of `inputs` values
+    pub weights: Vec<f32>,
+    pub bias: Vec<f32>,
+    #[serde(default)]
+    pub activation: Activation,
+}
+
+impl Layer {
+    fn forward(&self, input: &[f32], out: &mut Vec<f32>) {
+        out.clear();
+        out.extend(self.weights.chunks_exact(self.inputs).zip(&self.bias).map(|(row, bias)| {
+            let sum: f32 = row.iter().zip(input).map(|(w, x)| w * x).sum();
+            self.activation.apply(sum + bias)
+        }));
+    }
+}
+
+// json: { "layers": [ { "inputs", "outputs", "weights", "bias", "activation" }, ... ] }
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Mlp {
+    pub layers: Vec<Layer>,
+}
+
+impl Mlp {
+    pub fn inputs(&self) -> usize {
+        self.layers.first().map_or(0, |layer| layer.inputs)
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.layers.last().map_or(0, |layer| layer.outputs)
+    }
+
+    // Checks that the shapes of consecutive layers line up
+    pub fn validate(&self) -> anyhow::Result<()> {
+        ensure!(!self.layers.is_empty(), "network has no layers");
+        for (i, layer) in self.layers.iter().enumerate() {
+            ensure!(layer.inputs > 0 && layer.outputs > 0, "layer {} is empty", i);
+            ensure!(
+                layer.weights.len() == layer.inputs * layer.outputs,
+                "layer {} has {} weights, expected {}x{}", i, layer.weights.len(), layer.outputs, layer.inputs
+            );
+            ensure!(
+                layer.bias.len() == layer.outputs,
+                "layer {} has {} biases, expected {}", i, layer.bias.len(), layer.outputs
+            );
+            if let Some(next) = self.layers.get(i + 1) {
+                ensure!(
+                    next.inputs == layer.outputs,
+                    "layer {} outputs {} values but layer {} takes {}", i, layer.outputs, i + 1, next.inputs
+                );
+            }
+        }
+        Ok(())
+    }
+
+    // Missing inputs count as 0, extra ones are ignored
+    pub fn forward(&self, input: &[f32]) -> Vec<f32> {
+        let mut current = input.to_vec();
+        current.resize(self.inputs(), 0.0);
+        let mut next = Vec::new();
+        for layer in &self.layers {
+            layer.forward(&current, &mut next);
+            std::mem::swap(&mut current, &mut next);
+        }
+        current
+    }
+
+    pub fn from_json(text: &str) -> anyhow::Result<Self> {
+        let mlp: Mlp = serde_json::from_str(text)?;
+        mlp.validate()?;
+        Ok(mlp)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let mut reader = Reader { bytes, pos: 0 };
+        ensure!(reader.take(4)? == MAGIC, "not an MLP1 weight file");
+        let count = reader.u32()?;
+        let mut layers = Vec::new();
+        for i in 0..count {
+            let inputs = reader.u32()? as usize;
+            let outputs = reader.u32()? as usize;
+            let code = reader.u32()?;
+            let Some(activation) = Activation::from_code(code) else {
+                bail!("layer {} has unknown activation {}", i, code);
+            };
+            let weights = reader.f32s(inputs.saturating_mul(outputs))?;
+            let bias = reader.f32s(outputs)?;
+            layers.push(Layer { inputs, outputs, weights, bias, activation });
+        }
+        ensure!(reader.pos == bytes.len(), "{} trailing bytes after the last layer", bytes.len() - reader.pos);
+        let mlp = Mlp { layers };
+        mlp.validate()?;
+        Ok(mlp)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.extend((self.layers.len() as u32).to_le_bytes());
+        for layer in &self.layers {
+            out.extend((layer.inputs as u32).to_le_bytes());
+            out.extend((layer.outputs as u32).to_le_bytes());
+            out.extend(layer.activation.code().to_le_bytes());
+            layer.weights.iter().chain(&layer.bias).for_each(|value| out.extend(value.to_le_bytes()));
+        }
+        out
+    }
+
+    // `.json` files are parsed as json, anything else as the binary format
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("unable to read weight file {}", path.display()))?;
+        let mlp = if path.extension().is_some_and(|ext| ext == "json") {
+            std::str::from_utf8(&bytes).map_err(anyhow::Error::from).and_then(Self::from_json)
+        } else {
+            Self::from_bytes(&bytes)
+        };
+        mlp.with_context(|| format!("unable to parse weight file {}", path.display()))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let bytes = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::to_vec(self)?
+        } else {
+            self.to_bytes()
+        };
+        std::fs::write(path, bytes)
+            .with_context(|| format!("unable to write weight file {}", path.display()))
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        let Some(bytes) = self.bytes.get(self.pos..self.pos.saturating_add(len)) else {
+            bail!("unexpected end of file at byte {}", self.pos);
+        };
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f32s(&mut self, count: usize) -> anyhow::Result<Vec<f32>> {
+        let bytes = self.take(count.saturating_mul(4))?;
+        Ok(bytes.chunks_exact(4).map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap())).collect())
+    }
+}
+
+// A network mapping `observe` to `decode_action`
+pub struct MlpPolicy {
+    pub mlp: Mlp,
+}
+
+impl MlpPolicy {
+    pub fn new(mlp: Mlp) -> anyhow::Result<Self> {
+        mlp.validate()?;
+        ensure!(mlp.inputs() == OBS_LEN, "policy takes {} inputs, observations have {}", mlp.inputs(), OBS_LEN);
+        ensure!(mlp.outputs() == ACTION_LEN, "policy gives {} outputs, actions have {}", mlp.outputs(), ACTION_LEN);
+        Ok(Self { mlp })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        Self::new(Mlp::load(path)?)
+    }
+
+    pub fn act(&self, state: &GameState, conf: &GameConfig) -> TeamAction {
+        decode_action(&self.mlp.forward(&observe(state, conf)))
+    }
+
+    // `on_tick` states are already mirrored for us, so no `observe_as` is needed
+    pub fn into_strategy(
+        self,
+        on_reset: impl Fn(&TeamPair<u32>) -> [Vec2; NUM_PLAYERS as usize] + 'static,
+    ) -> Strategy {
+        Strategy {
+            on_reset: Box::new(on_reset),
+            on_tick: Box::new(move |state, _history| self.act(state, get_config())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(inputs: usize, outputs: usize, activation: Activation) -> Layer {
+        let weights = (0..inputs * outputs).map(|i| (i as f32 * 0.37).sin()).collect();
+        let bias = (0..outputs).map(|i| i as f32 * -0.25).collect();
+        Layer { inputs, outputs, weights, bias, activation }
+    }
+
+    #[test]
+    fn json_binary_json_round_trip() {
+        let mlp = Mlp { layers: vec![layer(3, 5, Activation::Relu), layer(5, 4, Activation::Tanh), layer(4, 2, Activation::Linear)] };
+        let json = serde_json::to_string(&mlp).unwrap();
+
+        let from_json = Mlp::from_json(&json).unwrap();
+        let from_bytes = Mlp::from_bytes(&from_json.to_bytes()).unwrap();
+        assert_eq!(from_bytes, mlp);
+        assert_eq!(serde_json::to_string(&from_bytes).unwrap(), json);
+
+        let input = [0.5, -1.0, 2.0];
+        assert_eq!(from_bytes.forward(&input), mlp.forward(&input));
+    }
+
+    #[test]
+    fn policy_rejects_bad_hidden_shapes() {
+        // the outer sizes fit, but the hidden layers do not line up
+        let mlp = Mlp { layers: vec![layer(OBS_LEN, 8, Activation::Relu), layer(6, ACTION_LEN, Activation::Tanh)] };
+        assert!(MlpPolicy::new(mlp).is_err());
+
+        let mut short = layer(OBS_LEN, ACTION_LEN, Activation::Tanh);
+        short.weights.pop();
+        assert!(MlpPolicy::new(Mlp { layers: vec![short] }).is_err());
+
+        assert!(MlpPolicy::new(Mlp { layers: vec![layer(OBS_LEN, ACTION_LEN, Activation::Tanh)] }).is_ok());
+    }
+}
@@ -0,0 +1,8 @@
+#![allow(dead_code)]
+
+// Training support: a gym-style environment over `core::sim`, the feature layout shared by
+// training dumps and in-game inference, and the inference itself
+
+pub mod features;
+pub mod env;
+pub mod mlp;
@@ -1,5 +1,7 @@
 mod core;
 mod strategy;
+mod tools;
+mod learn;
 
 use std::env::args;
 use std::path::PathBuf;
@@ -14,18 +16,30 @@ async fn main() {
 }
 
 async fn run() -> anyhow::Result<()> {
-    let args = args();
-    if args.len() < 2 {
+    let argv: Vec<String> = args().collect();
+    if argv.len() < 2 {
         println!("usage: [bin name] [shmem path]");
+        println!("{}", tools::tune::USAGE);
+        println!("{}", tools::tournament::USAGE);
+        println!("{}", tools::rollouts::USAGE);
+        println!("{}", tools::report::USAGE);
         return Ok(());
     }
 
-    let path = PathBuf::from(&args.skip(1).next().unwrap());
+    match argv[1].as_str() {
+        "tune" => return tools::tune::run(&argv[2..]),
+        "tournament" => return tools::tournament::run(&argv[2..]),
+        "rollouts" => return tools::rollouts::run(&argv[2..]),
+        "report" => return tools::report::run(&argv[2..]),
+        _ => (),
+    }
+
+    let path = PathBuf::from(&argv[1]);
     let chan = EngineChannel::from_path(path)?;
 
     let team = chan.handle_handshake().await?;
 
-    let strat = get_strategy(team);
+    let strat = get_strategy(team)?;
 
     loop {
         chan.handle_msg(&strat).await;
@@ -0,0 +1,609 @@
+#![allow(dead_code)]
+
+// A small behavior tree engine, one tree per player. Trees are re-evaluated from the root every
+// tick; leaves read the game state and write the player's action.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::core::*;
+use super::keeper::{ KeeperConfig, keeper_action };
+use super::shooting::{ ShotConfig, best_shot };
+use super::dribble::{ Dribble, DribbleConfig, plan_dribble };
+use super::endgame::dist_to_goal;
+use super::marking::mark_position;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Status {
+    Success,
+    Failure,
+    Running,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Value {
+    Float(f32),
+    Pos(Vec2),
+    Flag(bool),
+    Player(PlayerId),
+}
+
+// Per-tree memory shared by all of its nodes, it survives between ticks
+#[derive(Clone, Default, Debug)]
+pub struct Blackboard {
+    values: HashMap<&'static str, Value>,
+}
+
+impl Blackboard {
+    pub fn set(&mut self, key: &'static str, value: Value) {
+        self.values.insert(key, value);
+    }
+
+    pub fn get(&self, key: &str) -> Option<Value> {
+        self.values.get(key).copied()
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.values.remove(key);
+    }
+
+    pub fn float(&self, key: &str) -> Option<f32> {
+        match self.get(key)? {
+            Value::Float(it) => Some(it),
+            _ => None,
+        }
+    }
+
+    pub fn pos(&self, key: &str) -> Option<Vec2> {
+        match self.get(key)? {
+            Value::Pos(it) => Some(it),
+            _ => None,
+        }
+    }
+
+    pub fn flag(&self, key: &str) -> Option<bool> {
+        match self.get(key)? {
+            Value::Flag(it) => Some(it),
+            _ => None,
+        }
+    }
+
+    pub fn player(&self, key: &str) -> Option<PlayerId> {
+        match self.get(key)? {
+            Value::Player(it) => Some(it),
+            _ => None,
+        }
+    }
+}
+
+pub struct Context<'a> {
+    pub state: &'a GameState,
+    pub conf: &'a GameConfig,
+    pub id: PlayerId,
+    pub action: PlayerAction,
+    pub blackboard: &'a mut Blackboard,
+    path: Vec<&'static str>,
+    active: String,
+}
+
+impl Context<'_> {
+    pub fn me(&self) -> &PlayerState {
+        &self.state.players[self.id as usize]
+    }
+
+    pub fn has_ball(&self) -> bool {
+        self.state.ball_owner() == Some(self.id)
+    }
+}
+
+pub trait Node {
+    fn name(&self) -> &'static str;
+    fn tick(&mut self, ctx: &mut Context) -> Status;
+
+    // composites and decorators are not reported as the active branch
+    fn is_leaf(&self) -> bool {
+        true
+    }
+}
+
+pub type BoxNode = Box<dyn Node>;
+
+// Ticks a child while keeping track of the branch that produced the action
+pub fn run(node: &mut dyn Node, ctx: &mut Context) -> Status {
+    ctx.path.push(node.name());
+    let status = node.tick(ctx);
+    if status != Status::Failure && node.is_leaf() {
+        ctx.active = ctx.path.join(" > ");
+    }
+    ctx.path.pop();
+    status
+}
+
+// Ticks children in order until one fails or is still running
+pub struct Sequence {
+    pub name: &'static str,
+    pub children: Vec<BoxNode>,
+}
+
+impl Node for Sequence {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn tick(&mut self, ctx: &mut Context) -> Status {
+        for child in self.children.iter_mut() {
+            match run(child.as_mut(), ctx) {
+                Status::Success => continue,
+                status => return status,
+            }
+        }
+        Status::Success
+    }
+
+    fn is_leaf(&self) -> bool {
+        false
+    }
+}
+
+// Ticks children in order until one succeeds or is still running
+pub struct Selector {
+    pub name: &'static str,
+    pub children: Vec<BoxNode>,
+}
+
+impl Node for Selector {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn tick(&mut self, ctx: &mut Context) -> Status {
+        for child in self.children.iter_mut() {
+            match run(child.as_mut(), ctx) {
+                Status::Failure => continue,
+                status => return status,
+            }
+        }
+        Status::Failure
+    }
+
+    fn is_leaf(&self) -> bool {
+        false
+    }
+}
+
+// Ticks every child, succeeds once `success_threshold` children succeeded and fails once that
+// is no longer possible. Later children write over the action of earlier ones.
+pub struct Parallel {
+    pub name: &'static str,
+    pub success_threshold: usize,
+    pub children: Vec<BoxNode>,
+}
+
+impl Node for Parallel {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn tick(&mut self, ctx: &mut Context) -> Status {
+        let (mut successes, mut failures) = (0, 0);
+        for child in self.children.iter_mut() {
+            match run(child.as_mut(), ctx) {
+                Status::Success => successes += 1,
+                Status::Failure => failures += 1,
+                Status::Running => (),
+            }
+        }
+        if successes >= self.success_threshold {
+            Status::Success
+        } else if self.children.len() - failures < self.success_threshold {
+            Status::Failure
+        } else {
+            Status::Running
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        false
+    }
+}
+
+pub struct Inverter(pub BoxNode);
+
+impl Node for Inverter {
+    fn name(&self) -> &'static str {
+        "not"
+    }
+
+    fn tick(&mut self, ctx: &mut Context) -> Status {
+        match run(self.0.as_mut(), ctx) {
+            Status::Success => Status::Failure,
+            Status::Failure => Status::Success,
+            Status::Running => Status::Running,
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        false
+    }
+}
+
+pub struct ForceSuccess(pub BoxNode);
+
+impl Node for ForceSuccess {
+    fn name(&self) -> &'static str {
+        "force success"
+    }
+
+    fn tick(&mut self, ctx: &mut Context) -> Status {
+        run(self.0.as_mut(), ctx);
+        Status::Success
+    }
+
+    fn is_leaf(&self) -> bool {
+        false
+    }
+}
+
+// Only runs the child while the condition holds, fails otherwise
+pub struct Guard {
+    pub name: &'static str,
+    pub condition: fn(&Context) -> bool,
+    pub child: BoxNode,
+}
+
+impl Node for Guard {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn tick(&mut self, ctx: &mut Context) -> Status {
+        if (self.condition)(ctx) {
+            run(self.child.as_mut(), ctx)
+        } else {
+            Status::Failure
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        false
+    }
+}
+
+// Fails for `ticks` game ticks after its child succeeded
+pub struct Cooldown {
+    pub ticks: u32,
+    pub child: BoxNode,
+    ready_at: u32,
+}
+
+impl Cooldown {
+    pub fn new(ticks: u32, child: BoxNode) -> Self {
+        Self { ticks, child, ready_at: 0 }
+    }
+}
+
+impl Node for Cooldown {
+    fn name(&self) -> &'static str {
+        "cooldown"
+    }
+
+    fn tick(&mut self, ctx: &mut Context) -> Status {
+        if ctx.state.tick < self.ready_at {
+            return Status::Failure;
+        }
+        let status = run(self.child.as_mut(), ctx);
+        if status == Status::Success {
+            self.ready_at = ctx.state.tick + self.ticks;
+        }
+        status
+    }
+
+    fn is_leaf(&self) -> bool {
+        false
+    }
+}
+
+pub struct Condition {
+    pub name: &'static str,
+    pub check: fn(&Context) -> bool,
+}
+
+impl Node for Condition {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn tick(&mut self, ctx: &mut Context) -> Status {
+        if (self.check)(ctx) {
+            Status::Success
+        } else {
+            Status::Failure
+        }
+    }
+}
+
+pub fn has_ball(ctx: &Context) -> bool {
+    ctx.has_ball()
+}
+
+pub fn team_has_ball(ctx: &Context) -> bool {
+    matches!(ctx.state.ball_possession, BallPossessionState::Possessed { team: Team::Me, .. })
+}
+
+pub fn ball_is_loose(ctx: &Context) -> bool {
+    !matches!(ctx.state.ball_possession, BallPossessionState::Possessed { .. })
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Target {
+    Point(Vec2),
+    // a `Value::Pos` stored on the blackboard
+    Key(&'static str),
+}
+
+// Walks towards a point, succeeds once within `tolerance` of it
+pub struct MoveTo {
+    pub target: Target,
+    pub tolerance: f32,
+}
+
+impl Node for MoveTo {
+    fn name(&self) -> &'static str {
+        "move to"
+    }
+
+    fn tick(&mut self, ctx: &mut Context) -> Status {
+        let target = match self.target {
+            Target::Point(it) => it,
+            Target::Key(key) => match ctx.blackboard.pos(key) {
+                Some(it) => it,
+                None => return Status::Failure,
+            },
+        };
+        let delta = target - ctx.me().pos;
+        if delta.norm() <= self.tolerance {
+            ctx.action.dir = Vec2::ZERO;
+            Status::Success
+        } else {
+            ctx.action.dir = delta;
+            Status::Running
+        }
+    }
+}
+
+// Keeps goal with `keeper::keeper_action`, never fails
+pub struct Goalkeeper {
+    pub config: KeeperConfig,
+}
+
+impl Node for Goalkeeper {
+    fn name(&self) -> &'static str {
+        "goalkeeper"
+    }
+
+    fn tick(&mut self, ctx: &mut Context) -> Status {
+        let decision = keeper_action(ctx.state, ctx.conf, ctx.id, &self.config);
+        ctx.action = decision.action;
+        ctx.blackboard.set("keeper_target", Value::Pos(decision.target));
+        Status::Running
+    }
+}
+
+// Runs at the ball until we have it
+pub struct ChaseBall;
+
+impl Node for ChaseBall {
+    fn name(&self) -> &'static str {
+        "chase ball"
+    }
+
+    fn tick(&mut self, ctx: &mut Context) -> Status {
+        if ctx.has_ball() {
+            return Status::Success;
+        }
+        ctx.action.dir = ctx.state.ball.pos - ctx.me().pos;
+        Status::Running
+    }
+}
+
+// Passes to the teammate whose nearest opponent is the farthest away, preferring teammates
+// further up the field. Fails without the ball.
+pub struct PassToTeammate {
+    // teammates behind us by more than this are not considered
+    pub max_back: f32,
+}
+
+impl Node for PassToTeammate {
+    fn name(&self) -> &'static str {
+        "pass"
+    }
+
+    fn tick(&mut self, ctx: &mut Context) -> Status {
+        if !ctx.has_ball() {
+            return Status::Failure;
+        }
+        let me = ctx.me().clone();
+        let openness = |p: &PlayerState| {
+            ctx.state.players[Team::Other]
+                .iter()
+                .map(|o| o.pos.dist(&p.pos))
+                .fold(f32::INFINITY, f32::min)
+        };
+        let target = ctx.state.players[Team::Me]
+            .iter()
+            .filter(|p| p.id != me.id && p.pos.x >= me.pos.x - self.max_back)
+            .max_by(|a, b| (openness(a) + a.pos.x).total_cmp(&(openness(b) + b.pos.x)));
+
+        match target {
+            Some(teammate) => {
+                ctx.action.pass = pass(teammate.pos - me.pos);
+                ctx.blackboard.set("pass_target", Value::Player(teammate.id));
+                Status::Success
+            },
+            None => Status::Failure,
+        }
+    }
+}
+
+// Shoots at the best aim point (see `shooting::best_shot`) when within `range` of the goal and
+// the chance of scoring is at least `min_chance`. Fails without the ball.
+pub struct Shoot {
+    pub range: f32,
+    pub min_chance: f32,
+}
+
+impl Node for Shoot {
+    fn name(&self) -> &'static str {
+        "shoot"
+    }
+
+    fn tick(&mut self, ctx: &mut Context) -> Status {
+        if !ctx.has_ball() || dist_to_goal(ctx.me().pos, Team::Other, ctx.state.tick, ctx.conf) > self.range {
+            return Status::Failure;
+        }
+        let shot = best_shot(ctx.state, ctx.conf, ctx.id, &ShotConfig::default());
+        if shot.probability < self.min_chance {
+            return Status::Failure;
+        }
+        ctx.action.pass = pass(shot.dir);
+        ctx.blackboard.set("shot_chance", Value::Float(shot.probability));
+        Status::Success
+    }
+}
+
+// Carries the ball towards a target with `dribble::plan_dribble`, passing when that is better.
+// Fails without the ball.
+pub struct DribbleTo {
+    pub target: Target,
+    pub config: DribbleConfig,
+}
+
+impl Node for DribbleTo {
+    fn name(&self) -> &'static str {
+        "dribble"
+    }
+
+    fn tick(&mut self, ctx: &mut Context) -> Status {
+        let target = match self.target {
+            Target::Point(it) => it,
+            Target::Key(key) => match ctx.blackboard.pos(key) {
+                Some(it) => it,
+                None => return Status::Failure,
+            },
+        };
+        if !ctx.has_ball() {
+            return Status::Failure;
+        }
+        let plan = plan_dribble(ctx.state, ctx.conf, ctx.id, target, &self.config);
+        ctx.action = plan.action();
+        match plan {
+            Dribble::Pass { to, .. } => {
+                ctx.blackboard.set("pass_target", Value::Player(to));
+                Status::Success
+            },
+            Dribble::Carry { .. } => Status::Running,
+        }
+    }
+}
+
+// Stands goal-side of an opponent: the one stored under `key` on the blackboard, or else the
+// opponent closest to our goal
+pub struct MarkOpponent {
+    pub key: Option<&'static str>,
+}
+
+impl Node for MarkOpponent {
+    fn name(&self) -> &'static str {
+        "mark"
+    }
+
+    fn tick(&mut self, ctx: &mut Context) -> Status {
+        let goal = ctx.conf.field.goal_self();
+        let opponent = match self.key.and_then(|key| ctx.blackboard.player(key)) {
+            Some(id) => ctx.state.players.get(id as usize),
+            None => ctx.state.players[Team::Other]
+                .iter()
+                .min_by(|a, b| a.pos.dist_sq(&goal).total_cmp(&b.pos.dist_sq(&goal))),
+        };
+        let Some(opponent) = opponent else {
+            return Status::Failure;
+        };
+
+        ctx.action.dir = mark_position(opponent, ctx.conf) - ctx.me().pos;
+        Status::Running
+    }
+}
+
+// Stands still
+pub struct Hold;
+
+impl Node for Hold {
+    fn name(&self) -> &'static str {
+        "hold"
+    }
+
+    fn tick(&mut self, ctx: &mut Context) -> Status {
+        ctx.action.dir = Vec2::ZERO;
+        Status::Success
+    }
+}
+
+pub struct BehaviorTree {
+    pub root: BoxNode,
+    pub blackboard: Blackboard,
+    active: String,
+}
+
+impl BehaviorTree {
+    pub fn new(root: BoxNode) -> Self {
+        Self { root, blackboard: Blackboard::default(), active: String::new() }
+    }
+
+    pub fn tick(&mut self, state: &GameState, conf: &GameConfig, id: PlayerId) -> (PlayerAction, Status) {
+        let mut ctx = Context {
+            state,
+            conf,
+            id,
+            action: PlayerAction::default(),
+            blackboard: &mut self.blackboard,
+            path: Vec::new(),
+            active: String::new(),
+        };
+        let status = run(self.root.as_mut(), &mut ctx);
+        self.active = ctx.active;
+        (ctx.action, status)
+    }
+
+    // e.g. "root > attack > shoot", empty when every branch failed
+    pub fn active_branch(&self) -> &str {
+        &self.active
+    }
+}
+
+// One tree per player, printing the active branch of every player each tick when `debug` is set
+pub struct TeamTrees {
+    pub trees: [BehaviorTree; NUM_PLAYERS as usize],
+    pub debug: bool,
+}
+
+impl TeamTrees {
+    pub fn tick(&mut self, state: &GameState, conf: &GameConfig) -> [PlayerAction; NUM_PLAYERS as usize] {
+        let actions = std::array::from_fn(|id| self.trees[id].tick(state, conf, id as PlayerId).0);
+        if self.debug {
+            for (id, tree) in self.trees.iter().enumerate() {
+                println!("tick {} player {}: {}", state.tick, id, tree.active_branch());
+            }
+        }
+        actions
+    }
+
+    pub fn into_strategy(
+        self,
+        on_reset: impl Fn(&TeamPair<u32>) -> [Vec2; NUM_PLAYERS as usize] + 'static,
+    ) -> Strategy {
+        let trees = RefCell::new(self);
+        Strategy {
+            on_reset: Box::new(on_reset),
+            on_tick: Box::new(move |state, _history| trees.borrow_mut().tick(state, get_config())),
+        }
+    }
+}
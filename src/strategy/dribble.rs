@@ -0,0 +1,161 @@
+#![allow(dead_code)]
+
+// Carrying the ball towards a target. A carrier is slowed by `possession_slowdown` and can only be
+// tackled once its `capture_ticks` window is over, so every heading is played out against the
+// opponents: those already heading for the carrier chase it, the others keep their direction.
+// Headings that stay out of reach once the window closes and get closest to the target win.
+// When carrying is not safe, or a teammate is much closer to the target and can be reached
+// safely, passing wins.
+
+use crate::core::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DribbleConfig {
+    // headings tried around the carrier, besides the one straight at the target
+    pub directions: usize,
+    // ticks each heading is played out for
+    pub horizon: u32,
+    // distance a pass has to gain over carrying to be preferred while carrying is safe
+    pub pass_gain: f32,
+}
+
+impl Default for DribbleConfig {
+    fn default() -> Self {
+        Self {
+            directions: 16,
+            horizon: 20,
+            pass_gain: 100.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Dribble {
+    // `clearance` is how far the closest opponent stays outside its pickup reach once the
+    // capture window is over, negative when it gets the ball
+    Carry { dir: Vec2, clearance: f32 },
+    Pass { to: PlayerId, dir: Vec2 },
+}
+
+impl Dribble {
+    pub fn action(&self) -> PlayerAction {
+        match *self {
+            Dribble::Carry { dir, .. } => PlayerAction { dir, pass: no_pass() },
+            Dribble::Pass { dir, .. } => PlayerAction { dir: Vec2::ZERO, pass: pass(dir) },
+        }
+    }
+}
+
+// Ticks left before opponents in reach can take the ball off `carrier`
+pub fn capture_window(state: &GameState, conf: &GameConfig, carrier: PlayerId) -> u32 {
+    match state.ball_possession {
+        BallPossessionState::Possessed { owner, capture_ticks, .. } if owner == carrier => {
+            conf.ball.capture_ticks.saturating_sub(capture_ticks)
+        },
+        _ => 0,
+    }
+}
+
+// Plays a heading out: returns where the carrier ends up and its clearance
+fn play_out(state: &GameState, conf: &GameConfig, carrier: PlayerId, dir: Vec2, config: &DribbleConfig) -> (Vec2, f32) {
+    let me = &state.players[carrier as usize];
+    let team = state.player_team(carrier).unwrap_or(Team::Me);
+    let window = capture_window(state, conf, carrier);
+    let speed = me.speed * conf.player.possession_slowdown;
+    let size = conf.field.bottom_right();
+
+    let mut pos = me.pos;
+    // position, velocity (none for chasers), pickup reach
+    let mut opponents: Vec<(Vec2, Option<Vec2>, f32)> = state.players[team.other()]
+        .iter()
+        .map(|p| {
+            let dir = p.dir.normalize_or_zero();
+            let chasing = dir.dot((me.pos - p.pos).normalize_or_zero()) > 0.7;
+            (p.pos, (!chasing).then_some(dir * p.speed), p.pickup_radius)
+        })
+        .collect();
+    let chase_speed = conf.player.speed;
+    let mut clearance = f32::INFINITY;
+    for tick in 1..=config.horizon {
+        pos += dir * speed;
+        pos.x = pos.x.clamp(me.radius, size.x - me.radius);
+        pos.y = pos.y.clamp(me.radius, size.y - me.radius);
+        for (opponent, vel, _) in opponents.iter_mut() {
+            *opponent += vel.unwrap_or_else(|| (pos - *opponent).normalize_or_zero() * chase_speed);
+        }
+        if tick > window {
+            for &(opponent, _, reach) in &opponents {
+                clearance = clearance.min(opponent.dist(&pos) - reach);
+            }
+        }
+    }
+    (pos, clearance)
+}
+
+// Ticks until `receiver` gets a pass from `from`, if it gets there before any opponent
+pub fn pass_arrival(state: &GameState, conf: &GameConfig, from: Vec2, receiver: PlayerId) -> Option<u32> {
+    let target = &state.players[receiver as usize];
+    let team = state.player_team(receiver)?;
+    let dir = (target.pos - from).normalize_or_zero();
+    let decay = conf.ball.decay();
+    let batch = PlayerBatch::new(&state.players);
+    let opponents = PlayerMask::team(team.other());
+    let mut ball = from;
+    let mut speed = conf.player.pass_speed;
+    for tick in 1..=200u32 {
+        ball += dir * speed;
+        speed *= decay;
+        let reaching = batch.reaching(ball, tick as f32);
+        if !reaching.and(opponents).is_empty() {
+            return None;
+        }
+        if reaching.contains(receiver) {
+            return Some(tick);
+        }
+        if speed < EPSILON {
+            return None;
+        }
+    }
+    None
+}
+
+// The safe pass leaving the ball closest to `target`
+fn best_pass(state: &GameState, conf: &GameConfig, carrier: PlayerId, target: Vec2) -> Option<(PlayerId, f32)> {
+    let team = state.player_team(carrier)?;
+    let from = state.ball.pos;
+    state.players[team]
+        .iter()
+        .filter(|p| p.id != carrier && pass_arrival(state, conf, from, p.id).is_some())
+        .map(|p| (p.id, p.pos.dist(&target)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+pub fn plan_dribble(state: &GameState, conf: &GameConfig, carrier: PlayerId, target: Vec2, config: &DribbleConfig) -> Dribble {
+    let me = &state.players[carrier as usize];
+    let straight = (target - me.pos).normalize_or_zero();
+    let headings = std::iter::once(straight).chain(
+        (0..config.directions).map(|i| Vec2::from_angle_rad(2.0 * PI * i as f32 / config.directions.max(1) as f32)),
+    );
+
+    // safe headings first, then the closest to the target, then the most clearance
+    let (dir, end, clearance) = headings
+        .map(|dir| {
+            let (end, clearance) = play_out(state, conf, carrier, dir, config);
+            (dir, end, clearance)
+        })
+        .max_by(|a, b| {
+            (a.2 >= 0.0)
+                .cmp(&(b.2 >= 0.0))
+                .then(b.1.dist(&target).total_cmp(&a.1.dist(&target)))
+                .then(a.2.total_cmp(&b.2))
+        })
+        .unwrap();
+
+    if let Some((to, dist)) = best_pass(state, conf, carrier, target) {
+        let gain = end.dist(&target) - dist;
+        if clearance < 0.0 || gain > config.pass_gain {
+            return Dribble::Pass { to, dir: state.players[to as usize].pos - state.ball.pos };
+        }
+    }
+    Dribble::Carry { dir, clearance }
+}
@@ -0,0 +1,91 @@
+#![allow(dead_code)]
+
+// Endgame helpers. Once `tick > max_ticks` both goals grow to the full field height, so the whole
+// back line scores: distances to goal are measured to the closest point of the mouth instead of
+// its center, shots go for the widest stretch of the line no opponent can cut off, and the
+// defenders spread out in front of our back line so that between them they cover all of it.
+
+use crate::core::*;
+use super::keeper::cover_position;
+
+// Closest point of a team's goal mouth
+pub fn closest_goal_point(pos: Vec2, team: Team, tick: u32, conf: &GameConfig) -> Vec2 {
+    Segment::goal_mouth_at(conf, team, tick).closest_point(pos)
+}
+
+pub fn dist_to_goal(pos: Vec2, team: Team, tick: u32, conf: &GameConfig) -> f32 {
+    pos.dist(&closest_goal_point(pos, team, tick, conf))
+}
+
+// The stretch of the vertical line at `line_x` hidden from `from` by a disc, unbounded when the
+// disc is not between the two
+fn shadow(from: Vec2, center: Vec2, radius: f32, line_x: f32) -> Option<(f32, f32)> {
+    let ahead = line_x - from.x;
+    let to = center - from;
+    if to.x * ahead.signum() <= 0.0 || to.x.abs() > ahead.abs() + radius {
+        return None;
+    }
+    let dist = to.norm();
+    if dist <= radius {
+        return Some((f32::NEG_INFINITY, f32::INFINITY));
+    }
+    let angle = (to.y / to.x.abs()).atan();
+    let half = (radius / dist).asin();
+    let at = |a: f32| {
+        if a.abs() >= PI * 0.5 - EPSILON {
+            a.signum() * f32::INFINITY
+        } else {
+            from.y + ahead.abs() * a.tan()
+        }
+    };
+    Some((at(angle - half), at(angle + half)))
+}
+
+// Stretches of the other goal mouth that no opponent can get in front of a ball passed from
+// `from`, as (low y, high y), widest first. Opponents count with their pickup reach.
+pub fn open_segments(state: &GameState, conf: &GameConfig, from: Vec2) -> Vec<(f32, f32)> {
+    let Segment { a: low, b: high } = Segment::goal_mouth_at(conf, Team::Other, state.tick);
+    let margin = conf.ball.radius;
+    let mut shadows: Vec<(f32, f32)> = state.players[Team::Other]
+        .iter()
+        .filter_map(|p| shadow(from, p.pos, p.radius + p.pickup_radius, low.x))
+        .collect();
+    shadows.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut open = Vec::new();
+    let mut start = low.y + margin;
+    for (from_y, to_y) in shadows {
+        if from_y > start {
+            open.push((start, from_y.min(high.y - margin)));
+        }
+        start = start.max(to_y);
+    }
+    if start < high.y - margin {
+        open.push((start, high.y - margin));
+    }
+    open.retain(|(a, b)| b > a);
+    open.sort_by(|a, b| (b.1 - b.0).total_cmp(&(a.1 - a.0)));
+    open
+}
+
+// Middle of the widest open stretch of the other goal mouth
+pub fn widest_gap(state: &GameState, conf: &GameConfig, from: Vec2) -> Option<Vec2> {
+    let line_x = Segment::goal_mouth_at(conf, Team::Other, state.tick).a.x;
+    open_segments(state, conf, from).first().map(|&(a, b)| Vec2::new(line_x, (a + b) * 0.5))
+}
+
+// Spots for `defenders` players covering our whole goal mouth against a shot from `ball`: the
+// mouth is split in equal parts and each defender takes the `cover_position` of its part
+pub fn back_line_cover(ball: Vec2, tick: u32, conf: &GameConfig, defenders: usize, reach: f32, max_depth: f32) -> Vec<Vec2> {
+    let Segment { a: low, b: high } = Segment::goal_mouth_at(conf, Team::Me, tick);
+    let count = defenders.max(1);
+    (0..defenders)
+        .map(|i| {
+            let part_low = low + (high - low) * (i as f32 / count as f32);
+            let part_high = low + (high - low) * ((i + 1) as f32 / count as f32);
+            let mut pos = cover_position(ball, part_low, part_high, reach, max_depth, conf);
+            pos.y = pos.y.clamp(part_low.y, part_high.y);
+            pos
+        })
+        .collect()
+}
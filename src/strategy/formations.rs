@@ -0,0 +1,170 @@
+#![allow(dead_code)]
+
+use crate::core::*;
+use super::roles::Role;
+
+// A team shape in normalized field coordinates: (0, 0) is the top left corner and (1, 1) is
+// `FieldConfig::bottom_right()`. Our goal is always on the left.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Formation {
+    pub name: &'static str,
+    pub roles: [Role; NUM_PLAYERS as usize],
+    // kickoff positions, these have to stay on our half (x < 0.5)
+    pub slots: [Vec2; NUM_PLAYERS as usize],
+    // how far each slot shifts with the ball during play, per axis. 0 keeps the slot fixed,
+    // 1 moves it as far as the ball moved from the center
+    pub follow: [Vec2; NUM_PLAYERS as usize],
+}
+
+const KEEPER_FOLLOW: Vec2 = Vec2 { x: 0.05, y: 0.3 };
+
+pub const DIAMOND: Formation = Formation {
+    name: "1-2-1",
+    roles: [Role::Goalkeeper, Role::Defender(0), Role::Defender(1), Role::Attacker(0)],
+    slots: [
+        Vec2 { x: 0.08, y: 0.5 },
+        Vec2 { x: 0.25, y: 0.3 },
+        Vec2 { x: 0.25, y: 0.7 },
+        Vec2 { x: 0.42, y: 0.5 },
+    ],
+    follow: [
+        KEEPER_FOLLOW,
+        Vec2 { x: 0.6, y: 0.4 },
+        Vec2 { x: 0.6, y: 0.4 },
+        Vec2 { x: 0.8, y: 0.6 },
+    ],
+};
+
+pub const TWO_UP: Formation = Formation {
+    name: "1-1-2",
+    roles: [Role::Goalkeeper, Role::Defender(0), Role::Attacker(0), Role::Attacker(1)],
+    slots: [
+        Vec2 { x: 0.08, y: 0.5 },
+        Vec2 { x: 0.25, y: 0.5 },
+        Vec2 { x: 0.42, y: 0.3 },
+        Vec2 { x: 0.42, y: 0.7 },
+    ],
+    follow: [
+        KEEPER_FOLLOW,
+        Vec2 { x: 0.6, y: 0.5 },
+        Vec2 { x: 0.8, y: 0.4 },
+        Vec2 { x: 0.8, y: 0.4 },
+    ],
+};
+
+pub const BACK_LINE: Formation = Formation {
+    name: "1-3-0",
+    roles: [Role::Goalkeeper, Role::Defender(0), Role::Defender(1), Role::Defender(2)],
+    slots: [
+        Vec2 { x: 0.05, y: 0.5 },
+        Vec2 { x: 0.18, y: 0.25 },
+        Vec2 { x: 0.18, y: 0.5 },
+        Vec2 { x: 0.18, y: 0.75 },
+    ],
+    follow: [
+        KEEPER_FOLLOW,
+        Vec2 { x: 0.3, y: 0.3 },
+        Vec2 { x: 0.3, y: 0.5 },
+        Vec2 { x: 0.3, y: 0.3 },
+    ],
+};
+
+pub const ALL_OUT_ATTACK: Formation = Formation {
+    name: "all-out attack",
+    roles: [Role::Midfielder(0), Role::Attacker(0), Role::Attacker(1), Role::Attacker(2)],
+    slots: [
+        Vec2 { x: 0.3, y: 0.5 },
+        Vec2 { x: 0.45, y: 0.2 },
+        Vec2 { x: 0.45, y: 0.5 },
+        Vec2 { x: 0.45, y: 0.8 },
+    ],
+    follow: [
+        Vec2 { x: 0.8, y: 0.5 },
+        Vec2 { x: 1.0, y: 0.3 },
+        Vec2 { x: 1.0, y: 0.6 },
+        Vec2 { x: 1.0, y: 0.3 },
+    ],
+};
+
+pub const FORMATIONS: &[Formation] = &[DIAMOND, TWO_UP, BACK_LINE, ALL_OUT_ATTACK];
+
+pub fn find_formation(name: &str) -> Option<&'static Formation> {
+    FORMATIONS.iter().find(|it| it.name == name)
+}
+
+pub fn to_field(normalized: Vec2, field: &FieldConfig) -> Vec2 {
+    let size = field.bottom_right();
+    Vec2::new(normalized.x * size.x, normalized.y * size.y)
+}
+
+fn to_normalized(pos: Vec2, field: &FieldConfig) -> Vec2 {
+    let size = field.bottom_right();
+    Vec2::new(pos.x / size.x, pos.y / size.y)
+}
+
+impl Formation {
+    // what to return from `on_reset`
+    pub fn reset_positions(&self, field: &FieldConfig) -> [Vec2; NUM_PLAYERS as usize] {
+        self.slots.map(|slot| to_field(slot, field))
+    }
+
+    // Slot positions during play, shifted towards the ball
+    pub fn live_positions(&self, field: &FieldConfig, ball: Vec2) -> [Vec2; NUM_PLAYERS as usize] {
+        let offset = to_normalized(ball, field) - Vec2::new(0.5, 0.5);
+        std::array::from_fn(|i| {
+            let slot = self.slots[i] + Vec2::new(offset.x * self.follow[i].x, offset.y * self.follow[i].y);
+            to_field(Vec2::new(slot.x.clamp(0.0, 1.0), slot.y.clamp(0.0, 1.0)), field)
+        })
+    }
+
+    pub fn slot_of(&self, role: Role) -> Option<usize> {
+        self.roles.iter().position(|&it| it == role)
+    }
+
+    pub fn live_position(&self, role: Role, field: &FieldConfig, ball: Vec2) -> Option<Vec2> {
+        self.slot_of(role).map(|slot| self.live_positions(field, ball)[slot])
+    }
+}
+
+// Picks a formation from the score, the time left and where the ball is
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct FormationSelector {
+    // ticks before the end of the match (endgame included) that count as "late"
+    pub late_ticks: u32,
+    // goal lead at which we stop pushing forward
+    pub comfortable_lead: i32,
+    // the ball counts as in our third below this normalized x
+    pub defensive_third: f32,
+}
+
+impl Default for FormationSelector {
+    fn default() -> Self {
+        Self {
+            late_ticks: 600,
+            comfortable_lead: 2,
+            defensive_third: 1.0 / 3.0,
+        }
+    }
+}
+
+impl FormationSelector {
+    // `on_reset` does not know the tick nor the ball, pass the last tick seen in `on_tick` and
+    // the field center in that case
+    pub fn select(&self, conf: &GameConfig, score: &TeamPair<u32>, tick: u32, ball: Vec2) -> &'static Formation {
+        let lead = score.me as i32 - score.other as i32;
+        let end = conf.max_ticks + conf.endgame_ticks;
+        let late = end.saturating_sub(tick) <= self.late_ticks.max(conf.endgame_ticks);
+        let endgame = tick > conf.max_ticks;
+        let ball_x = to_normalized(ball, &conf.field).x;
+
+        if lead < 0 && (late || endgame) {
+            &ALL_OUT_ATTACK
+        } else if lead > 0 && late {
+            &BACK_LINE
+        } else if lead >= self.comfortable_lead || ball_x < self.defensive_third {
+            &DIAMOND
+        } else {
+            &TWO_UP
+        }
+    }
+}
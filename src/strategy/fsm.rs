@@ -0,0 +1,263 @@
+#![allow(dead_code)]
+
+// Team-level tactics as a state machine. Every tactic owns a handler producing the whole team's
+// actions; machines are handlers themselves, so a tactic can be refined by a nested machine
+// (e.g. an endgame machine with its own attack and defend handlers).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::core::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Tactic {
+    KickoffSetup,
+    Attack,
+    Defend,
+    Transition,
+    Counterpress,
+    Endgame,
+}
+
+pub struct TacticContext<'a> {
+    pub state: &'a GameState,
+    pub conf: &'a GameConfig,
+    pub tactic: Tactic,
+    // tick at which the current tactic was entered
+    pub entered_at: u32,
+}
+
+impl TacticContext<'_> {
+    pub fn ticks_in_tactic(&self) -> u32 {
+        self.state.tick.saturating_sub(self.entered_at)
+    }
+}
+
+pub trait TacticHandler {
+    fn on_enter(&mut self, _ctx: &TacticContext) {}
+    fn on_exit(&mut self, _ctx: &TacticContext) {}
+    fn tick(&mut self, ctx: &TacticContext) -> TeamAction;
+}
+
+impl<F> TacticHandler for F where F: FnMut(&TacticContext) -> TeamAction {
+    fn tick(&mut self, ctx: &TacticContext) -> TeamAction {
+        self(ctx)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TransitionRules {
+    // tick after which `Endgame` takes over, disable for machines nested inside `Endgame`
+    pub endgame: bool,
+    // how long we keep `KickoffSetup` at most, if nobody touches the ball
+    pub kickoff_ticks: u32,
+    // how long after losing the ball we try to win it straight back
+    pub counterpress_ticks: u32,
+}
+
+impl Default for TransitionRules {
+    fn default() -> Self {
+        Self {
+            endgame: true,
+            kickoff_ticks: 30,
+            counterpress_ticks: 40,
+        }
+    }
+}
+
+// What the rules get to look at besides the state
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TransitionInput<'a> {
+    pub state: &'a GameState,
+    pub conf: &'a GameConfig,
+    pub current: Tactic,
+    pub entered_at: u32,
+    // last tick at which we had the ball or were passing it
+    pub last_ours: Option<u32>,
+    // whether the previous tick was already a kickoff
+    pub was_kickoff: bool,
+}
+
+pub fn is_kickoff(state: &GameState, conf: &GameConfig) -> bool {
+    state.is_ball_free()
+        && state.ball.vel.norm() < EPSILON
+        && state.ball.pos.dist(&conf.field.center()) < EPSILON
+}
+
+impl TransitionRules {
+    // Next tactic and why, checked in order: endgame, kickoff, then possession
+    pub fn next(&self, input: &TransitionInput) -> (Tactic, &'static str) {
+        let state = input.state;
+        let lead = state.score.me as i32 - state.score.other as i32;
+
+        if self.endgame && state.tick > input.conf.max_ticks {
+            return (Tactic::Endgame, "past max ticks");
+        }
+        if is_kickoff(state, input.conf) {
+            let waited = state.tick.saturating_sub(input.entered_at);
+            if !input.was_kickoff {
+                return (Tactic::KickoffSetup, "ball on the center spot");
+            }
+            if input.current == Tactic::KickoffSetup && waited < self.kickoff_ticks {
+                return (Tactic::KickoffSetup, "waiting for kickoff");
+            }
+        }
+
+        match state.ball_possession {
+            BallPossessionState::Possessed { team: Team::Me, .. } => (Tactic::Attack, "we have the ball"),
+            BallPossessionState::Passing { team: Team::Me } => (Tactic::Attack, "we are passing"),
+            BallPossessionState::Possessed { team: Team::Other, .. } | BallPossessionState::Passing { team: Team::Other } => {
+                // chase harder when behind, drop back straight away when ahead
+                let window = match lead {
+                    l if l < 0 => self.counterpress_ticks * 2,
+                    l if l > 0 => 0,
+                    _ => self.counterpress_ticks,
+                };
+                let just_lost = input
+                    .last_ours
+                    .is_some_and(|tick| state.tick.saturating_sub(tick) <= window);
+                if just_lost && matches!(input.current, Tactic::Attack | Tactic::Transition | Tactic::Counterpress) {
+                    (Tactic::Counterpress, "just lost the ball")
+                } else {
+                    (Tactic::Defend, "they have the ball")
+                }
+            },
+            BallPossessionState::Free => (Tactic::Transition, "ball is free"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TransitionRecord {
+    pub tick: u32,
+    pub from: Tactic,
+    pub to: Tactic,
+    pub reason: &'static str,
+}
+
+pub struct TacticsMachine {
+    pub name: &'static str,
+    pub rules: TransitionRules,
+    handlers: HashMap<Tactic, Box<dyn TacticHandler>>,
+    current: Tactic,
+    entered_at: u32,
+    last_ours: Option<u32>,
+    was_kickoff: bool,
+    started: bool,
+    // print every transition as it happens
+    pub log: bool,
+    pub history: Vec<TransitionRecord>,
+}
+
+impl TacticsMachine {
+    pub fn new(name: &'static str, rules: TransitionRules) -> Self {
+        Self {
+            name,
+            rules,
+            handlers: HashMap::new(),
+            current: Tactic::KickoffSetup,
+            entered_at: 0,
+            last_ours: None,
+            was_kickoff: false,
+            started: false,
+            log: false,
+            history: Vec::new(),
+        }
+    }
+
+    // Tactics without a handler leave every player standing still
+    pub fn with(mut self, tactic: Tactic, handler: impl TacticHandler + 'static) -> Self {
+        self.handlers.insert(tactic, Box::new(handler));
+        self
+    }
+
+    pub fn current(&self) -> Tactic {
+        self.current
+    }
+
+    fn context<'a>(&self, state: &'a GameState, conf: &'a GameConfig) -> TacticContext<'a> {
+        TacticContext { state, conf, tactic: self.current, entered_at: self.entered_at }
+    }
+
+    fn enter(&mut self, tactic: Tactic, state: &GameState, conf: &GameConfig) {
+        self.current = tactic;
+        self.entered_at = state.tick;
+        let ctx = self.context(state, conf);
+        if let Some(handler) = self.handlers.get_mut(&tactic) {
+            handler.on_enter(&ctx);
+        }
+    }
+
+    fn exit(&mut self, state: &GameState, conf: &GameConfig) {
+        let ctx = self.context(state, conf);
+        if let Some(handler) = self.handlers.get_mut(&self.current) {
+            handler.on_exit(&ctx);
+        }
+    }
+
+    pub fn update(&mut self, state: &GameState, conf: &GameConfig) -> TeamAction {
+        if matches!(
+            state.ball_possession,
+            BallPossessionState::Possessed { team: Team::Me, .. } | BallPossessionState::Passing { team: Team::Me }
+        ) {
+            self.last_ours = Some(state.tick);
+        }
+
+        let input = TransitionInput {
+            state,
+            conf,
+            current: self.current,
+            entered_at: self.entered_at,
+            last_ours: self.last_ours,
+            was_kickoff: self.was_kickoff,
+        };
+        let (next, reason) = self.rules.next(&input);
+        self.was_kickoff = is_kickoff(state, conf);
+
+        if !self.started {
+            self.started = true;
+            self.enter(next, state, conf);
+        } else if next != self.current {
+            let record = TransitionRecord { tick: state.tick, from: self.current, to: next, reason };
+            if self.log {
+                println!("[{}] tick {}: {:?} -> {:?} ({})", self.name, record.tick, record.from, record.to, record.reason);
+            }
+            self.history.push(record);
+            self.exit(state, conf);
+            self.enter(next, state, conf);
+        }
+
+        let ctx = self.context(state, conf);
+        match self.handlers.get_mut(&self.current) {
+            Some(handler) => handler.tick(&ctx),
+            None => Default::default(),
+        }
+    }
+
+    pub fn into_strategy(
+        self,
+        on_reset: impl Fn(&TeamPair<u32>) -> [Vec2; NUM_PLAYERS as usize] + 'static,
+    ) -> Strategy {
+        let machine = RefCell::new(self);
+        Strategy {
+            on_reset: Box::new(on_reset),
+            on_tick: Box::new(move |state, _history| machine.borrow_mut().update(state, get_config())),
+        }
+    }
+}
+
+// A nested machine starts over every time its parent tactic is entered
+impl TacticHandler for TacticsMachine {
+    fn on_enter(&mut self, _ctx: &TacticContext) {
+        self.started = false;
+        self.last_ours = None;
+        self.was_kickoff = false;
+    }
+
+    fn on_exit(&mut self, ctx: &TacticContext) {
+        self.exit(ctx.state, ctx.conf);
+    }
+
+    fn tick(&mut self, ctx: &TacticContext) -> TeamAction {
+        self.update(ctx.state, ctx.conf)
+    }
+}
@@ -0,0 +1,207 @@
+#![allow(dead_code)]
+
+// Grids over the field for positioning logic. A `Grid` holds one value per square cell, sized
+// from `FieldConfig`. Float grids double as influence maps: players and the ball splat gaussian
+// bumps onto them, maps combine cell by cell, are read back between cell centers with bilinear
+// sampling, and the best cell of a region is one call away, e.g. the most open spot of the
+// attacking third is the argmax of `control_map` inside `attacking_third`. Cells are indexed
+// by (col, row).
+
+use std::ops::{ Index, IndexMut };
+use crate::core::*;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Grid<T> {
+    pub cols: usize,
+    pub rows: usize,
+    // side of a cell
+    pub cell: f32,
+    // row-major
+    pub data: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    // Covers the whole field, the last row and column may stick out of it
+    pub fn new(field: &FieldConfig, cell: f32, fill: T) -> Self {
+        let cell = cell.max(1.0);
+        let size = field.bottom_right();
+        let cols = (size.x / cell).ceil().max(1.0) as usize;
+        let rows = (size.y / cell).ceil().max(1.0) as usize;
+        Self { cols, rows, cell, data: vec![fill; cols * rows] }
+    }
+
+    pub fn fill(&mut self, value: T) {
+        self.data.iter_mut().for_each(|it| *it = value.clone());
+    }
+
+    pub fn same_shape<U>(&self, other: &Grid<U>) -> bool {
+        self.cols == other.cols && self.rows == other.rows && self.cell == other.cell
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn get(&self, col: usize, row: usize) -> Option<&T> {
+        (col < self.cols && row < self.rows).then(|| &self.data[row * self.cols + col])
+    }
+
+    pub fn get_mut(&mut self, col: usize, row: usize) -> Option<&mut T> {
+        (col < self.cols && row < self.rows).then(|| &mut self.data[row * self.cols + col])
+    }
+
+    // Cell containing `pos`, none outside the grid
+    pub fn cell_of(&self, pos: Vec2) -> Option<(usize, usize)> {
+        if pos.x < 0.0 || pos.y < 0.0 {
+            return None;
+        }
+        let (col, row) = ((pos.x / self.cell) as usize, (pos.y / self.cell) as usize);
+        (col < self.cols && row < self.rows).then_some((col, row))
+    }
+
+    // Same, but outside the grid the closest border cell
+    pub fn nearest_cell(&self, pos: Vec2) -> (usize, usize) {
+        let col = ((pos.x / self.cell).max(0.0) as usize).min(self.cols - 1);
+        let row = ((pos.y / self.cell).max(0.0) as usize).min(self.rows - 1);
+        (col, row)
+    }
+
+    // The up to eight cells touching a cell
+    pub fn neighbours(&self, col: usize, row: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let (col, row) = (col as isize, row as isize);
+        (-1..=1isize)
+            .flat_map(move |dr| (-1..=1isize).map(move |dc| (col + dc, row + dr)))
+            .filter(move |&(c, r)| (c, r) != (col, row) && c >= 0 && r >= 0 && c < self.cols as isize && r < self.rows as isize)
+            .map(|(c, r)| (c as usize, r as usize))
+    }
+
+    pub fn center(&self, col: usize, row: usize) -> Vec2 {
+        Vec2::new((col as f32 + 0.5) * self.cell, (row as f32 + 0.5) * self.cell)
+    }
+
+    // Every cell as (col, row, value)
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize, &T)> {
+        self.data.iter().enumerate().map(|(i, it)| (i % self.cols, i / self.cols, it))
+    }
+
+    // Column and row ranges of the cells whose center is inside `rect`
+    fn cells_in(&self, rect: &Rect) -> (std::ops::Range<usize>, std::ops::Range<usize>) {
+        let first = |min: f32, len: usize| ((min / self.cell - 0.5).ceil().max(0.0) as usize).min(len);
+        let last = |max: f32, len: usize| (((max / self.cell - 0.5).floor() + 1.0).max(0.0) as usize).min(len);
+        (
+            first(rect.min.x, self.cols)..last(rect.max.x, self.cols),
+            first(rect.min.y, self.rows)..last(rect.max.y, self.rows),
+        )
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (col, row): (usize, usize)) -> &T {
+        assert!(col < self.cols && row < self.rows, "cell ({}, {}) outside a {}x{} grid", col, row, self.cols, self.rows);
+        &self.data[row * self.cols + col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (col, row): (usize, usize)) -> &mut T {
+        assert!(col < self.cols && row < self.rows, "cell ({}, {}) outside a {}x{} grid", col, row, self.cols, self.rows);
+        &mut self.data[row * self.cols + col]
+    }
+}
+
+impl Grid<f32> {
+    // Bilinear between the four closest cell centers, edge cells extend to the border
+    pub fn sample(&self, pos: Vec2) -> f32 {
+        let fx = (pos.x / self.cell - 0.5).clamp(0.0, (self.cols - 1) as f32);
+        let fy = (pos.y / self.cell - 0.5).clamp(0.0, (self.rows - 1) as f32);
+        let (c0, r0) = (fx.floor() as usize, fy.floor() as usize);
+        let (c1, r1) = ((c0 + 1).min(self.cols - 1), (r0 + 1).min(self.rows - 1));
+        let (tx, ty) = (fx - c0 as f32, fy - r0 as f32);
+        let at = |c: usize, r: usize| self.data[r * self.cols + c];
+        let top = at(c0, r0) * (1.0 - tx) + at(c1, r0) * tx;
+        let bottom = at(c0, r1) * (1.0 - tx) + at(c1, r1) * tx;
+        top * (1.0 - ty) + bottom * ty
+    }
+
+    // Adds `amount * exp(-d² / 2σ²)` to every cell center within 3σ of `pos`
+    pub fn splat(&mut self, pos: Vec2, sigma: f32, amount: f32) {
+        let sigma = sigma.max(EPSILON);
+        let reach = sigma * 3.0;
+        let area = Rect::new(pos - Vec2::new(reach, reach), pos + Vec2::new(reach, reach));
+        let (cols, rows) = self.cells_in(&area);
+        let inv = 1.0 / (2.0 * sigma * sigma);
+        for row in rows {
+            for col in cols.clone() {
+                let d_sq = self.center(col, row).dist_sq(&pos);
+                self.data[row * self.cols + col] += amount * (-d_sq * inv).exp();
+            }
+        }
+    }
+
+    pub fn splat_players<'a>(&mut self, players: impl IntoIterator<Item = &'a PlayerState>, sigma: f32, amount: f32) {
+        for player in players {
+            self.splat(player.pos, sigma, amount);
+        }
+    }
+
+    // Cell by cell, both grids need the same shape
+    pub fn combine(&mut self, other: &Grid<f32>, op: impl Fn(f32, f32) -> f32) {
+        assert!(self.same_shape(other), "combining grids of different shapes");
+        for (a, &b) in self.data.iter_mut().zip(&other.data) {
+            *a = op(*a, b);
+        }
+    }
+
+    pub fn add(&mut self, other: &Grid<f32>) {
+        self.combine(other, |a, b| a + b);
+    }
+
+    pub fn max(&mut self, other: &Grid<f32>) {
+        self.combine(other, f32::max);
+    }
+
+    pub fn multiply(&mut self, other: &Grid<f32>) {
+        self.combine(other, |a, b| a * b);
+    }
+
+    pub fn scale(&mut self, factor: f32) {
+        self.data.iter_mut().for_each(|it| *it *= factor);
+    }
+
+    // Center and value of the highest cell whose center is inside `region`
+    pub fn argmax(&self, region: &Rect) -> Option<(Vec2, f32)> {
+        let (cols, rows) = self.cells_in(region);
+        rows.flat_map(|row| cols.clone().map(move |col| (col, row)))
+            .map(|(col, row)| (self.center(col, row), self.data[row * self.cols + col]))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    pub fn argmin(&self, region: &Rect) -> Option<(Vec2, f32)> {
+        let (cols, rows) = self.cells_in(region);
+        rows.flat_map(|row| cols.clone().map(move |col| (col, row)))
+            .map(|(col, row)| (self.center(col, row), self.data[row * self.cols + col]))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+}
+
+// Positive where we are closer to control the space than the opponents, negative where they are
+pub fn control_map(state: &GameState, conf: &GameConfig, cell: f32, sigma: f32) -> Grid<f32> {
+    let mut map = Grid::new(&conf.field, cell, 0.0);
+    map.splat_players(&state.players[Team::Me], sigma, 1.0);
+    map.splat_players(&state.players[Team::Other], sigma, -1.0);
+    map
+}
+
+// The part of the field closest to the other goal
+pub fn attacking_third(field: &FieldConfig) -> Rect {
+    let size = field.bottom_right();
+    Rect::new(Vec2::new(size.x * 2.0 / 3.0, 0.0), size)
+}
+
+// Spot in `region` farthest, in influence, from every opponent, nudged towards the ball
+pub fn most_open_spot(state: &GameState, conf: &GameConfig, region: &Rect, cell: f32, sigma: f32) -> Option<Vec2> {
+    let mut map = Grid::new(&conf.field, cell, 0.0);
+    map.splat_players(&state.players[Team::Other], sigma, -1.0);
+    map.splat(state.ball.pos, sigma * 3.0, 0.25);
+    map.argmax(region).map(|(pos, _)| pos)
+}
@@ -0,0 +1,195 @@
+#![allow(dead_code)]
+
+// A goalkeeper for our goal on the left. It stands on the bisector of the shot angle the ball
+// has on our goal, just far enough out to cover both posts, goes for balls that are about to
+// cross the goal line, comes out for free balls in the box it gets to first, and clears the
+// ball to the most open teammate once it has it.
+
+use crate::core::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct KeeperConfig {
+    // how far the keeper may stand out from the goal line, as a fraction of the penalty box width
+    pub max_depth: f32,
+    // ticks the keeper needs to be ahead of the closest opponent to come out for a free ball
+    pub claim_margin: u32,
+    // ticks of ball trajectory looked at for an incoming shot
+    pub lookahead: u32,
+}
+
+impl Default for KeeperConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 0.8,
+            claim_margin: 5,
+            lookahead: 60,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum KeeperMode {
+    // on the shot angle bisector
+    Guard,
+    // in the way of a ball heading into the goal
+    Intercept,
+    // coming out for a free ball
+    Claim,
+    // has the ball and passes it away
+    Clear,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct KeeperDecision {
+    pub mode: KeeperMode,
+    pub target: Vec2,
+    pub action: PlayerAction,
+}
+
+// The box in front of our goal, its two corners on the field side rounded by `penalty_box_radius`
+pub fn in_penalty_box(pos: Vec2, conf: &GameConfig) -> bool {
+    let width = conf.goal.penalty_box_width as f32;
+    let half = conf.goal.penalty_box_height as f32 * 0.5;
+    let radius = (conf.goal.penalty_box_radius as f32).min(width).min(half);
+    let dy = (pos.y - conf.field.goal_self().y).abs();
+    if pos.x > width || dy > half {
+        return false;
+    }
+    if pos.x <= width - radius || dy <= half - radius {
+        return true;
+    }
+    Vec2::new(pos.x, dy).dist(&Vec2::new(width - radius, half - radius)) <= radius
+}
+
+// Closest the keeper stands to the goal line, in front of the goal frame
+fn min_depth(conf: &GameConfig) -> f32 {
+    conf.player.radius.max(conf.goal.thickness as f32)
+}
+
+// Where the ball is after `ticks` ticks if nobody touches it, ignoring bounces
+pub fn ball_after(ball: &BallState, ticks: u32, conf: &GameConfig) -> Vec2 {
+    let decay = conf.ball.decay();
+    let travelled = if (1.0 - decay).abs() < EPSILON {
+        ticks as f32
+    } else {
+        (1.0 - decay.powi(ticks as i32)) / (1.0 - decay)
+    };
+    ball.pos + ball.vel * travelled
+}
+
+// The point and tick at which a loose ball crosses our goal line inside the goal mouth, if it
+// gets there before friction stops it
+pub fn predict_goal_crossing(ball: &BallState, tick: u32, conf: &GameConfig, lookahead: u32) -> Option<(Vec2, u32)> {
+    if ball.vel.x >= 0.0 {
+        return None;
+    }
+    let Segment { a: low, b: high } = Segment::goal_mouth_at(conf, Team::Me, tick);
+    let mut prev = ball.pos;
+    for k in 1..=lookahead {
+        let pos = ball_after(ball, k, conf);
+        if pos.x <= 0.0 {
+            let t = prev.x / (prev.x - pos.x).max(EPSILON);
+            let crossing = prev + (pos - prev) * t;
+            return (crossing.y >= low.y && crossing.y <= high.y).then_some((crossing, k));
+        }
+        if pos.dist_sq(&prev) < EPSILON * EPSILON {
+            return None;
+        }
+        prev = pos;
+    }
+    None
+}
+
+// Spot on the bisector of the angle between the ball and a stretch of our goal line from `low`
+// to `high`, where a player reaching `reach` to both sides covers the whole angle. Kept between
+// the goal line and `max_depth`, not clamped vertically.
+pub fn cover_position(ball: Vec2, low: Vec2, high: Vec2, reach: f32, max_depth: f32, conf: &GameConfig) -> Vec2 {
+    let depth = min_depth(conf);
+    let to_low = (low - ball).normalize_or_zero();
+    let to_high = (high - ball).normalize_or_zero();
+    let bisector = (to_low + to_high).normalize_or_zero();
+
+    // behind the goal line or straight along it there is no angle to cover
+    if bisector.x >= -EPSILON {
+        return Vec2::new(depth, ball.y.clamp(low.y, high.y));
+    }
+    let half_angle = to_low.dot(to_high).clamp(-1.0, 1.0).acos() * 0.5;
+    let to_line = -ball.x / bisector.x;
+    let dist = if half_angle.sin() > EPSILON { reach / half_angle.sin() } else { to_line };
+    let mut pos = ball + bisector * dist.min(to_line);
+    pos.x = pos.x.clamp(depth, max_depth.max(depth));
+    pos
+}
+
+// `cover_position` for the whole goal, kept in front of the penalty box
+pub fn guard_position(ball: Vec2, tick: u32, conf: &GameConfig, reach: f32, max_depth: f32) -> Vec2 {
+    let Segment { a: low, b: high } = Segment::goal_mouth_at(conf, Team::Me, tick);
+    let center = conf.field.goal_self();
+    let mut pos = cover_position(ball, low, high, reach, max_depth, conf);
+    pos.y = pos.y.clamp(center.y - conf.goal.penalty_box_height as f32 * 0.5, center.y + conf.goal.penalty_box_height as f32 * 0.5);
+    pos
+}
+
+fn ticks_to_reach(player: &PlayerState, target: Vec2) -> u32 {
+    let dist = (player.pos.dist(&target) - player.pickup_radius).max(0.0);
+    (dist / player.speed.max(EPSILON)).ceil() as u32
+}
+
+// Pass to the teammate whose nearest opponent is the farthest away, or straight up the field
+// when there is nobody
+fn clear(state: &GameState, id: PlayerId, conf: &GameConfig) -> Vec2 {
+    let me = &state.players[id as usize];
+    let openness = |p: &PlayerState| {
+        state.players[Team::Other].iter().map(|o| o.pos.dist(&p.pos)).fold(f32::INFINITY, f32::min)
+    };
+    state.players[Team::Me]
+        .iter()
+        .filter(|p| p.id != id && p.pos.x > me.pos.x)
+        .max_by(|a, b| openness(a).total_cmp(&openness(b)))
+        .map(|p| p.pos - me.pos)
+        .unwrap_or(conf.field.goal_other() - me.pos)
+}
+
+pub fn keeper_action(state: &GameState, conf: &GameConfig, id: PlayerId, config: &KeeperConfig) -> KeeperDecision {
+    let me = &state.players[id as usize];
+    let ball = &state.ball;
+    let decide = |mode: KeeperMode, target: Vec2| KeeperDecision {
+        mode,
+        target,
+        action: PlayerAction { dir: target - me.pos, pass: no_pass() },
+    };
+
+    if state.ball_owner() == Some(id) {
+        let dir = clear(state, id, conf);
+        return KeeperDecision {
+            mode: KeeperMode::Clear,
+            target: me.pos + dir,
+            action: PlayerAction { dir, pass: pass(dir) },
+        };
+    }
+
+    let loose = !matches!(state.ball_possession, BallPossessionState::Possessed { .. });
+    if loose {
+        if let Some((crossing, ticks)) = predict_goal_crossing(ball, state.tick, conf, config.lookahead) {
+            // the first point of the path we can get to in time, the crossing itself otherwise
+            let target = (1..=ticks)
+                .map(|k| (k, ball_after(ball, k, conf)))
+                .find(|&(k, pos)| ticks_to_reach(me, pos) <= k)
+                .map_or(crossing, |(_, pos)| pos);
+            return decide(KeeperMode::Intercept, target);
+        }
+
+        let stop = ball_after(ball, config.lookahead, conf);
+        if in_penalty_box(stop, conf) {
+            let ours = ticks_to_reach(me, stop);
+            let theirs = state.players[Team::Other].iter().map(|o| ticks_to_reach(o, stop)).min().unwrap_or(u32::MAX);
+            if ours + config.claim_margin <= theirs {
+                return decide(KeeperMode::Claim, stop);
+            }
+        }
+    }
+
+    let reach = me.pickup_radius;
+    let max_depth = conf.goal.penalty_box_width as f32 * config.max_depth;
+    decide(KeeperMode::Guard, guard_position(ball.pos, state.tick, conf, reach, max_depth))
+}
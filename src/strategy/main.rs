@@ -1,28 +1,78 @@
 use crate::core::*;
+use super::params::*;
+use super::bt::*;
+use super::fsm::*;
+use super::formations::*;
+use super::roles::hungarian;
+use super::keeper::KeeperConfig;
+use super::dribble::DribbleConfig;
+use super::marking::Marking;
+use super::steering::{ steered, SteeringConfig };
+use super::setpiece::SetPieces;
+use super::endgame::back_line_cover;
+use super::shooting::{ ShotConfig, best_shot };
 
 // This function tells the engine what strategy you want your bot to use
-pub fn get_strategy(team: u8) -> Strategy {
+pub fn get_strategy(team: u8) -> anyhow::Result<Strategy> {
 
     // team == 0 means I am on the left
     // team == 1 means I am on the right
 
     if team == 0 {
         println!("Hello! I am team A (on the left)");
-        Strategy {
-            on_reset: Box::new(goalee_formation),
-            on_tick: Box::new(ball_chase),
-        }
+        // picks up the parameters written by `bot tune ball_chase`, if there are any
+        let def = find_strategy("ball_chase").unwrap();
+        Ok((def.build)(&Params::load_or_default(def.params_path(), def.params)?))
     } else {
         println!("Hello! I am team B (on the right)");
-        Strategy {
+        Ok(Strategy {
             on_reset: Box::new(goalee_formation),
             on_tick: Box::new(do_nothing),
-        }
+        })
     }
     // NOTE when actually submitting your bot, you probably want to have the SAME strategy for both
     // sides.
 }
 
+// Strategies that the offline tools (`bot tune`) can play against each other
+pub const STRATEGIES: &[StrategyDef] = &[
+    StrategyDef {
+        name: "ball_chase",
+        params: FORMATION_PARAMS,
+        build: |params| Strategy {
+            on_reset: Box::new(tuned_formation(params)),
+            on_tick: Box::new(ball_chase),
+        },
+    },
+    StrategyDef {
+        name: "behavior_tree",
+        params: &[],
+        build: |_| behavior_tree_team().into_strategy(goalee_formation),
+    },
+    StrategyDef {
+        name: "tactics",
+        params: &[],
+        build: |_| SetPieces::default().into_strategy(steered(tactics_machine().into_strategy(|score| {
+            let conf = get_config();
+            FormationSelector::default()
+                .select(conf, score, 0, conf.field.center())
+                .reset_positions(&conf.field)
+        }), SteeringConfig::default())),
+    },
+    StrategyDef {
+        name: "do_nothing",
+        params: &[],
+        build: |_| Strategy {
+            on_reset: Box::new(goalee_formation),
+            on_tick: Box::new(do_nothing),
+        },
+    },
+];
+
+pub fn find_strategy(name: &str) -> Option<&'static StrategyDef> {
+    STRATEGIES.iter().find(|def| def.name == name)
+}
+
 // The engine will call this function every time the field is reset:
 // either after a goal, if the ball has not moved for too long, or right before endgame
 fn goalee_formation(_score: &TeamPair<u32>) -> [Vec2; NUM_PLAYERS as usize] {
@@ -37,8 +87,31 @@ fn goalee_formation(_score: &TeamPair<u32>) -> [Vec2; NUM_PLAYERS as usize] {
     ]
 }
 
+const KEEPER_X: ParamSpec = ParamSpec::new("keeper_x", 0.1, 0.02, 0.3);
+const LINE_X: ParamSpec = ParamSpec::new("line_x", 0.4, 0.2, 0.5);
+const LINE_SPREAD: ParamSpec = ParamSpec::new("line_spread", 0.1, 0.0, 0.3);
+const FORMATION_PARAMS: &[ParamSpec] = &[KEEPER_X, LINE_X, LINE_SPREAD];
+
+// Same shape as `goalee_formation`, but with its constants declared as tunable parameters
+fn tuned_formation(params: &Params) -> impl Fn(&TeamPair<u32>) -> [Vec2; NUM_PLAYERS as usize] {
+    let keeper_x = params.get(&KEEPER_X);
+    let line_x = params.get(&LINE_X);
+    let spread = params.get(&LINE_SPREAD);
+
+    move |_score| {
+        let field = get_config().field.bottom_right();
+        [
+            Vec2::new(field.x * keeper_x, field.y * 0.5),
+            Vec2::new(field.x * line_x, field.y * (0.5 - spread)),
+            Vec2::new(field.x * line_x, field.y * 0.5),
+            Vec2::new(field.x * line_x, field.y * (0.5 + spread)),
+        ]
+    }
+}
+
 // Very simple strategy to chase the ball and shoot on goal
-fn ball_chase(state: &GameState) -> [PlayerAction; NUM_PLAYERS as usize] {
+// (`_history` holds the last few states, see `core/history.rs`)
+fn ball_chase(state: &GameState, _history: &StateHistory) -> [PlayerAction; NUM_PLAYERS as usize] {
     let conf = get_config();
 
     // NOTE Do not worry about what side your bot is on! 
@@ -51,8 +124,132 @@ fn ball_chase(state: &GameState) -> [PlayerAction; NUM_PLAYERS as usize] {
     })
 }
 
+// Keeper guards the goal, everyone else goes after the ball, marks while a teammate has it, and
+// shoots or passes when they have the ball
+fn behavior_tree_team() -> TeamTrees {
+    let conf = get_config();
+
+    let keeper = || -> BoxNode { Box::new(Goalkeeper { config: KeeperConfig::default() }) };
+    let field_player = || -> BoxNode {
+        Box::new(Selector {
+            name: "field",
+            children: vec![
+                Box::new(Guard {
+                    name: "attack",
+                    condition: has_ball,
+                    child: Box::new(Selector {
+                        name: "release",
+                        children: vec![
+                            Box::new(Shoot { range: conf.field.width as f32 * 0.3, min_chance: 0.2 }),
+                            Box::new(DribbleTo { target: Target::Point(conf.field.goal_other()), config: DribbleConfig::default() }),
+                        ],
+                    }),
+                }),
+                Box::new(Guard { name: "win ball", condition: |ctx| !team_has_ball(ctx), child: Box::new(ChaseBall) }),
+                Box::new(MarkOpponent { key: None }),
+            ],
+        })
+    };
+
+    TeamTrees {
+        trees: std::array::from_fn(|id| BehaviorTree::new(if id == 0 { keeper() } else { field_player() })),
+        debug: false,
+    }
+}
+
+// The `chasers` players closest to the ball go for it, the others take the slots of the current
+// formation. Whoever has the ball runs at goal and shoots from close range.
+fn formation_play(ctx: &TacticContext, chasers: usize) -> TeamAction {
+    let (state, conf) = (ctx.state, ctx.conf);
+    let ball = state.ball.pos;
+    let goal = conf.field.goal_other();
+    let formation = FormationSelector::default().select(conf, &state.score, state.tick, ball);
+    let slots = formation.live_positions(&conf.field, ball);
+
+    let mut by_ball: Vec<usize> = (0..NUM_PLAYERS as usize).collect();
+    by_ball.sort_by(|&a, &b| state.players[a].pos.dist_sq(&ball).total_cmp(&state.players[b].pos.dist_sq(&ball)));
+    let cost: Vec<Vec<f32>> = (0..NUM_PLAYERS as usize)
+        .map(|id| slots.iter().map(|slot| state.players[id].pos.dist(slot)).collect())
+        .collect();
+    let slot_of = hungarian(&cost);
+    let ours = matches!(state.ball_possession, BallPossessionState::Possessed { team: Team::Me, .. });
+
+    std::array::from_fn(|id| {
+        let pos = state.players[id].pos;
+        if state.ball_owner() == Some(id as u32) {
+            let shoot = pos.dist(&goal) < conf.field.width as f32 * 0.25;
+            PlayerAction { dir: goal - pos, pass: if shoot { pass(goal - pos) } else { no_pass() } }
+        } else if by_ball[..chasers].contains(&id) && !ours {
+            PlayerAction { dir: ball - pos, pass: no_pass() }
+        } else {
+            PlayerAction { dir: slots[slot_of[id]] - pos, pass: no_pass() }
+        }
+    })
+}
+
+// Like `formation_play` with one chaser, but the other outfield players mark opponents goal-side
+fn marking_defence() -> impl TacticHandler {
+    let mut marking = Marking::default();
+    move |ctx: &TacticContext| {
+        let (state, conf) = (ctx.state, ctx.conf);
+        let mut actions = formation_play(ctx, 1);
+        let ball = state.ball.pos;
+        let chaser = (0..NUM_PLAYERS as usize).min_by(|&a, &b| {
+            state.players[a].pos.dist_sq(&ball).total_cmp(&state.players[b].pos.dist_sq(&ball))
+        });
+        for mark in marking.assign(state, conf) {
+            let id = mark.player as usize;
+            if Some(id) != chaser && state.ball_owner() != Some(mark.player) {
+                actions[id].dir = mark.pos - state.players[id].pos;
+            }
+        }
+        actions
+    }
+}
+
+// The whole back line is a goal now. Leading, everyone but the closest player to the ball lines
+// up to cover our line; otherwise two players chase and the carrier shoots at the open stretches.
+fn endgame_play(ctx: &TacticContext) -> TeamAction {
+    let (state, conf) = (ctx.state, ctx.conf);
+    let lead = state.score.me as i32 - state.score.other as i32;
+    let mut actions = formation_play(ctx, if lead > 0 { 1 } else { 2 });
+
+    if let Some(owner) = state.ball_owner().filter(|&id| state.player_team(id) == Some(Team::Me)) {
+        let shot = best_shot(state, conf, owner, &ShotConfig::default());
+        if shot.probability >= 0.5 {
+            actions[owner as usize].pass = pass(shot.dir);
+        }
+    }
+    if lead > 0 {
+        let ball = state.ball.pos;
+        let mut defenders: Vec<usize> = (0..NUM_PLAYERS as usize).collect();
+        defenders.sort_by(|&a, &b| state.players[a].pos.dist_sq(&ball).total_cmp(&state.players[b].pos.dist_sq(&ball)));
+        let defenders = &defenders[1..];
+        let reach = conf.player.radius + conf.player.pickup_radius;
+        let spots = back_line_cover(ball, state.tick, conf, defenders.len(), reach, conf.goal.penalty_box_width as f32);
+        let cost: Vec<Vec<f32>> = defenders.iter().map(|&id| spots.iter().map(|spot| state.players[id].pos.dist(spot)).collect()).collect();
+        for (&id, spot) in defenders.iter().zip(hungarian(&cost)) {
+            if state.ball_owner() != Some(id as u32) {
+                actions[id].dir = spots[spot] - state.players[id].pos;
+            }
+        }
+    }
+    actions
+}
+
+// Team phases driving how many players go after the ball
+fn tactics_machine() -> TacticsMachine {
+    TacticsMachine::new("tactics", TransitionRules::default())
+        .with(Tactic::KickoffSetup, |ctx: &TacticContext| formation_play(ctx, 1))
+        .with(Tactic::Attack, |ctx: &TacticContext| formation_play(ctx, 0))
+        .with(Tactic::Defend, marking_defence())
+        .with(Tactic::Transition, |ctx: &TacticContext| formation_play(ctx, 2))
+        .with(Tactic::Counterpress, |ctx: &TacticContext| formation_play(ctx, 3))
+        .with(Tactic::Endgame, endgame_play)
+}
+
 // This strategy will do nothing :(
-fn do_nothing(_state: &GameState) -> [PlayerAction; NUM_PLAYERS as usize] {
+fn do_nothing(_state: &GameState, _history: &StateHistory) -> [PlayerAction; NUM_PLAYERS as usize] {
     std::array::from_fn(|_id| PlayerAction {
         dir: Vec2::ZERO,
         pass: no_pass()
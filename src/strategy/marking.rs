@@ -0,0 +1,200 @@
+#![allow(dead_code)]
+
+// Defensive coverage for our outfield players: each one either marks an opponent, standing
+// goal-side of it, or guards a zone of our half. The assignment is re-solved every tick with the
+// Hungarian algorithm and, like `RoleAssigner`, only changes once it gets cheaper by
+// `hysteresis`, so markers swap opponents when those cross but do not flicker in between.
+
+use crate::core::*;
+use super::roles::hungarian;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MarkingMode {
+    // the most dangerous opponents, one marker each
+    ManToMan,
+    // every marker guards a zone and marks whoever is inside it
+    Zonal,
+    // opponents near our goal or the ball are marked man to man, the rest guard zones
+    Hybrid,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MarkTarget {
+    Opponent(PlayerId),
+    // index into `MarkingConfig::zones`
+    Zone(usize),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MarkAssignment {
+    pub player: PlayerId,
+    pub target: MarkTarget,
+    pub pos: Vec2,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct MarkingConfig {
+    pub mode: MarkingMode,
+    // left out of the assignment
+    pub keeper: Option<PlayerId>,
+    // zone centers in normalized field coordinates, like formation slots
+    pub zones: Vec<Vec2>,
+    // how far zones shift with the ball, 0 keeps them fixed
+    pub zone_follow: f32,
+    // an opponent within this distance of a zone center is marked by its guard
+    pub zone_radius: f32,
+    // in hybrid mode, opponents this close to our goal or to the ball are marked man to man
+    pub danger_radius: f32,
+    pub hysteresis: f32,
+}
+
+impl Default for MarkingConfig {
+    fn default() -> Self {
+        Self {
+            mode: MarkingMode::Hybrid,
+            keeper: Some(0),
+            zones: vec![Vec2::new(0.2, 0.3), Vec2::new(0.15, 0.5), Vec2::new(0.2, 0.7)],
+            zone_follow: 0.3,
+            zone_radius: 120.0,
+            danger_radius: 250.0,
+            hysteresis: 30.0,
+        }
+    }
+}
+
+// space left between a marker and its opponent
+const MARK_MARGIN: f32 = 2.0;
+
+// Goal-side of the opponent, just clear of it towards our goal
+pub fn mark_position(opponent: &PlayerState, conf: &GameConfig) -> Vec2 {
+    let goal = conf.field.goal_self();
+    let gap = opponent.radius + conf.player.radius + MARK_MARGIN;
+    opponent.pos + (goal - opponent.pos).normalize_or_zero() * gap
+}
+
+pub struct Marking {
+    pub config: MarkingConfig,
+    current: Vec<(PlayerId, MarkTarget)>,
+}
+
+impl Marking {
+    pub fn new(config: MarkingConfig) -> Self {
+        Self { config, current: Vec::new() }
+    }
+
+    // forget the current assignment, e.g. after a reset
+    pub fn clear(&mut self) {
+        self.current.clear();
+    }
+
+    fn zone_center(&self, zone: usize, state: &GameState, conf: &GameConfig) -> Vec2 {
+        let size = conf.field.bottom_right();
+        let anchor = self.config.zones[zone];
+        let anchor = Vec2::new(anchor.x * size.x, anchor.y * size.y);
+        anchor + (state.ball.pos - conf.field.center()) * self.config.zone_follow
+    }
+
+    // Where the guard of a zone stands: marking the opponent in it closest to our goal, or on the
+    // line between the zone center and the ball
+    fn zone_position(&self, zone: usize, state: &GameState, conf: &GameConfig) -> Vec2 {
+        let center = self.zone_center(zone, state, conf);
+        let goal = conf.field.goal_self();
+        let intruder = state.players[Team::Other]
+            .iter()
+            .filter(|p| p.pos.dist(&center) <= self.config.zone_radius)
+            .min_by(|a, b| a.pos.dist_sq(&goal).total_cmp(&b.pos.dist_sq(&goal)));
+        match intruder {
+            Some(opponent) => mark_position(opponent, conf),
+            None => center + (state.ball.pos - center).normalize_or_zero() * (self.config.zone_radius * 0.25),
+        }
+    }
+
+    fn target_position(&self, target: MarkTarget, state: &GameState, conf: &GameConfig) -> Vec2 {
+        match target {
+            MarkTarget::Opponent(id) => mark_position(&state.players[id as usize], conf),
+            MarkTarget::Zone(zone) => self.zone_position(zone, state, conf),
+        }
+    }
+
+    // Targets to cover, most urgent first: opponents by distance to our goal, then zones
+    fn targets(&self, state: &GameState, conf: &GameConfig, markers: usize) -> Vec<MarkTarget> {
+        let goal = conf.field.goal_self();
+        let mut opponents: Vec<&PlayerState> = state.players[Team::Other].iter().collect();
+        opponents.sort_by(|a, b| a.pos.dist_sq(&goal).total_cmp(&b.pos.dist_sq(&goal)));
+        let zones = (0..self.config.zones.len()).map(MarkTarget::Zone);
+
+        match self.config.mode {
+            MarkingMode::ManToMan => opponents.iter().take(markers).map(|p| MarkTarget::Opponent(p.id)).collect(),
+            MarkingMode::Zonal => zones.collect(),
+            MarkingMode::Hybrid => {
+                let danger = |p: &PlayerState| {
+                    p.pos.dist(&goal) <= self.config.danger_radius || p.pos.dist(&state.ball.pos) <= self.config.danger_radius
+                };
+                opponents
+                    .iter()
+                    .filter(|p| danger(p))
+                    .take(markers)
+                    .map(|p| MarkTarget::Opponent(p.id))
+                    .chain(zones)
+                    .collect()
+            },
+        }
+    }
+
+    pub fn assign(&mut self, state: &GameState, conf: &GameConfig) -> Vec<MarkAssignment> {
+        let markers: Vec<PlayerId> = (0..NUM_PLAYERS).filter(|&id| Some(id) != self.config.keeper).collect();
+        let targets = self.targets(state, conf, markers.len());
+        let positions: Vec<Vec2> = targets.iter().map(|&target| self.target_position(target, state, conf)).collect();
+
+        // square matrix: missing markers are dummies costing nothing, and urgent targets get a
+        // bonus so the dummies end up with the least urgent ones
+        let n = markers.len().max(targets.len());
+        let urgency = conf.field.width as f32 * 10.0;
+        let cost: Vec<Vec<f32>> = (0..n)
+            .map(|row| {
+                (0..n)
+                    .map(|col| match (markers.get(row), positions.get(col)) {
+                        (Some(&id), Some(pos)) => state.players[id as usize].pos.dist(pos) - urgency * (n - col) as f32,
+                        _ => 0.0,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let best: Vec<(PlayerId, MarkTarget)> = hungarian(&cost)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(row, col)| Some((*markers.get(row)?, *targets.get(col)?)))
+            .collect();
+        let total = |assignment: &[(PlayerId, MarkTarget)]| -> Option<f32> {
+            assignment
+                .iter()
+                .map(|(id, target)| {
+                    let row = markers.iter().position(|it| it == id)?;
+                    let col = targets.iter().position(|it| it == target)?;
+                    Some(cost[row][col])
+                })
+                .sum()
+        };
+        let switch = match (total(&self.current), total(&best)) {
+            (Some(current), Some(best_total)) => {
+                self.current.len() != best.len() || current - best_total > self.config.hysteresis
+            },
+            _ => true,
+        };
+        if switch {
+            self.current = best;
+        }
+
+        self.current
+            .iter()
+            .map(|&(player, target)| MarkAssignment { player, target, pos: self.target_position(target, state, conf) })
+            .collect()
+    }
+}
+
+impl Default for Marking {
+    fn default() -> Self {
+        Self::new(MarkingConfig::default())
+    }
+}
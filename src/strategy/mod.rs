@@ -1,3 +1,19 @@
 mod main;
+pub mod params;
+pub mod roles;
+pub mod formations;
+pub mod bt;
+pub mod fsm;
+pub mod planner;
+pub mod opponents;
+pub mod keeper;
+pub mod shooting;
+pub mod dribble;
+pub mod marking;
+pub mod steering;
+pub mod pathing;
+pub mod setpiece;
+pub mod endgame;
+pub mod influence;
 
-pub use main::get_strategy;
+pub use main::{ get_strategy, find_strategy, STRATEGIES };
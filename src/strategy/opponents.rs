@@ -0,0 +1,236 @@
+#![allow(dead_code)]
+
+// Learns what the other team tends to do over the course of a match: where each opponent
+// spends its time, whether it runs at the ball, how often and where it passes, and how deep
+// their keeper stands. Feed it every state from `on_tick`, it keeps no reference to them.
+
+use crate::core::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct OpponentModelConfig {
+    // heatmap resolution over the whole field
+    pub heatmap_cols: usize,
+    pub heatmap_rows: usize,
+    // weight of the newest sample in the velocity and keeper depth averages
+    pub smoothing: f32,
+    // cosine between movement and the direction to the ball above which a tick counts as chasing
+    pub chase_cos: f32,
+    // share of moving ticks spent chasing for an opponent to be called a chaser
+    pub chaser_ratio: f32,
+    // moving ticks needed before `is_chaser` answers yes
+    pub min_samples: u32,
+}
+
+impl Default for OpponentModelConfig {
+    fn default() -> Self {
+        Self {
+            heatmap_cols: 12,
+            heatmap_rows: 8,
+            smoothing: 0.2,
+            chase_cos: 0.7,
+            chaser_ratio: 0.5,
+            min_samples: 30,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct OpponentStats {
+    // visits per cell, row-major
+    pub heatmap: Vec<u32>,
+    pub samples: u32,
+    // smoothed observed velocity, per tick
+    pub velocity: Vec2,
+    pub moving_ticks: u32,
+    pub chasing_ticks: u32,
+    pub possession_ticks: u32,
+    pub passes: u32,
+    // sum of the unit directions of all passes
+    pub pass_dir_sum: Vec2,
+}
+
+impl OpponentStats {
+    fn new(cells: usize) -> Self {
+        Self {
+            heatmap: vec![0; cells],
+            samples: 0,
+            velocity: Vec2::ZERO,
+            moving_ticks: 0,
+            chasing_ticks: 0,
+            possession_ticks: 0,
+            passes: 0,
+            pass_dir_sum: Vec2::ZERO,
+        }
+    }
+
+    pub fn chase_ratio(&self) -> f32 {
+        self.chasing_ticks as f32 / self.moving_ticks.max(1) as f32
+    }
+
+    // passes per tick spent on the ball
+    pub fn pass_rate(&self) -> f32 {
+        self.passes as f32 / self.possession_ticks.max(1) as f32
+    }
+
+    pub fn mean_pass_dir(&self) -> Option<Vec2> {
+        (self.passes > 0).then(|| self.pass_dir_sum / self.passes as f32)
+    }
+}
+
+pub struct OpponentModel {
+    pub config: OpponentModelConfig,
+    stats: Vec<OpponentStats>,
+    // distance of their deepest player from their goal line, smoothed
+    keeper_depth: Option<f32>,
+    prev: Option<GameState>,
+}
+
+impl OpponentModel {
+    pub fn new(mut config: OpponentModelConfig) -> Self {
+        config.heatmap_cols = config.heatmap_cols.max(1);
+        config.heatmap_rows = config.heatmap_rows.max(1);
+        let cells = config.heatmap_cols * config.heatmap_rows;
+        Self {
+            stats: (0..NUM_PLAYERS).map(|_| OpponentStats::new(cells)).collect(),
+            keeper_depth: None,
+            prev: None,
+            config,
+        }
+    }
+
+    // Forgets everything, e.g. between matches
+    pub fn clear(&mut self) {
+        *self = Self::new(self.config);
+    }
+
+    fn slot(id: PlayerId) -> Option<usize> {
+        (NUM_PLAYERS..NUM_PLAYERS * 2).contains(&id).then(|| (id - NUM_PLAYERS) as usize)
+    }
+
+    fn cell(&self, pos: Vec2, conf: &GameConfig) -> usize {
+        let size = conf.field.bottom_right();
+        let (cols, rows) = (self.config.heatmap_cols, self.config.heatmap_rows);
+        let col = ((pos.x / size.x * cols as f32) as usize).min(cols - 1);
+        let row = ((pos.y / size.y * rows as f32) as usize).min(rows - 1);
+        row * cols + col
+    }
+
+    // `state` is from our point of view, as handed to `on_tick`
+    pub fn observe(&mut self, state: &GameState, conf: &GameConfig) {
+        // a tick going backwards means a new match
+        if self.prev.as_ref().is_some_and(|prev| state.tick < prev.tick) {
+            self.clear();
+        }
+        let alpha = self.config.smoothing;
+        let others = &state.players[NUM_PLAYERS as usize..];
+
+        for (i, player) in others.iter().enumerate() {
+            let cell = self.cell(player.pos, conf);
+            let stats = &mut self.stats[i];
+            stats.heatmap[cell] += 1;
+            stats.samples += 1;
+            if state.ball_owner() == Some(player.id) {
+                stats.possession_ticks += 1;
+            }
+        }
+
+        let deepest = others.iter().map(|player| conf.field.width as f32 - player.pos.x).fold(f32::INFINITY, f32::min);
+        self.keeper_depth = Some(match self.keeper_depth {
+            Some(depth) => depth + alpha * (deepest - depth),
+            None => deepest,
+        });
+
+        let Some(prev) = self.prev.replace(state.clone()) else {
+            return;
+        };
+        let ticks = state.tick.saturating_sub(prev.tick).max(1) as f32;
+
+        for (i, player) in others.iter().enumerate() {
+            let before = &prev.players[NUM_PLAYERS as usize + i];
+            let vel = (player.pos - before.pos) / ticks;
+            // faster than anyone can run means the field was reset in between
+            if vel.norm() > conf.player.speed * 1.5 + EPSILON {
+                continue;
+            }
+            let stats = &mut self.stats[i];
+            stats.velocity += (vel - stats.velocity) * alpha;
+            // the carrier is on the ball already, whatever it does is not chasing
+            if vel.norm() > conf.player.speed * 0.2 && prev.ball_owner() != Some(before.id) {
+                stats.moving_ticks += 1;
+                let to_ball = (prev.ball.pos - before.pos).normalize_or_zero();
+                if vel.normalize_or_zero().dot(to_ball) > self.config.chase_cos {
+                    stats.chasing_ticks += 1;
+                }
+            }
+        }
+
+        // a pickup and an immediate pass can happen within one tick, so the passer is whoever had
+        // the ball or was closest to it
+        let started_pass = matches!(state.ball_possession, BallPossessionState::Passing { team: Team::Other })
+            && !matches!(prev.ball_possession, BallPossessionState::Passing { team: Team::Other });
+        if started_pass {
+            let passer = match prev.ball_possession {
+                BallPossessionState::Possessed { owner, team: Team::Other, .. } => Self::slot(owner),
+                _ => (0..NUM_PLAYERS as usize).min_by(|&a, &b| {
+                    let dist = |i: usize| prev.players[NUM_PLAYERS as usize + i].pos.dist_sq(&prev.ball.pos);
+                    dist(a).total_cmp(&dist(b))
+                }),
+            };
+            if let Some(i) = passer {
+                self.stats[i].passes += 1;
+                self.stats[i].pass_dir_sum += state.ball.vel.normalize_or_zero();
+            }
+        }
+    }
+
+    pub fn stats(&self, id: PlayerId) -> Option<&OpponentStats> {
+        Self::slot(id).map(|i| &self.stats[i])
+    }
+
+    // Current position extrapolated with the observed velocity, kept on the field
+    pub fn expected_position(&self, state: &GameState, id: PlayerId, ticks: u32, conf: &GameConfig) -> Option<Vec2> {
+        let stats = self.stats(id)?;
+        let pos = state.players[id as usize].pos + stats.velocity * ticks as f32;
+        let size = conf.field.bottom_right();
+        Some(Vec2::new(pos.x.clamp(0.0, size.x), pos.y.clamp(0.0, size.y)))
+    }
+
+    pub fn is_chaser(&self, id: PlayerId) -> bool {
+        self.stats(id).is_some_and(|stats| {
+            stats.moving_ticks >= self.config.min_samples && stats.chase_ratio() >= self.config.chaser_ratio
+        })
+    }
+
+    pub fn chasers(&self) -> usize {
+        (NUM_PLAYERS..NUM_PLAYERS * 2).filter(|&id| self.is_chaser(id)).count()
+    }
+
+    // Share of ticks the opponent spent in every cell, row-major
+    pub fn heatmap(&self, id: PlayerId) -> Option<Vec<f32>> {
+        let stats = self.stats(id)?;
+        let total = stats.samples.max(1) as f32;
+        Some(stats.heatmap.iter().map(|&n| n as f32 / total).collect())
+    }
+
+    // Center of the cell the opponent visited most
+    pub fn favourite_spot(&self, id: PlayerId, conf: &GameConfig) -> Option<Vec2> {
+        let stats = self.stats(id).filter(|stats| stats.samples > 0)?;
+        let cell = (0..stats.heatmap.len()).max_by_key(|&cell| stats.heatmap[cell])?;
+        let (cols, rows) = (self.config.heatmap_cols, self.config.heatmap_rows);
+        let size = conf.field.bottom_right();
+        Some(Vec2::new(
+            ((cell % cols) as f32 + 0.5) / cols as f32 * size.x,
+            ((cell / cols) as f32 + 0.5) / rows as f32 * size.y,
+        ))
+    }
+
+    pub fn keeper_depth(&self) -> Option<f32> {
+        self.keeper_depth
+    }
+}
+
+impl Default for OpponentModel {
+    fn default() -> Self {
+        Self::new(OpponentModelConfig::default())
+    }
+}
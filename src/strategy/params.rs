@@ -0,0 +1,112 @@
+#![allow(dead_code)]
+
+use anyhow::Context;
+use serde::{ Serialize, Deserialize };
+use std::collections::BTreeMap;
+use std::path::Path;
+use crate::core::*;
+
+// A numeric constant of a strategy that the tuner is allowed to change
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub default: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl ParamSpec {
+    pub const fn new(name: &'static str, default: f32, min: f32, max: f32) -> Self {
+        Self { name, default, min, max }
+    }
+
+    // maps a value in 0..=1 onto the range of the parameter
+    pub fn denormalize(&self, t: f32) -> f32 {
+        self.min + t.clamp(0.0, 1.0) * (self.max - self.min)
+    }
+
+    pub fn normalize(&self, value: f32) -> f32 {
+        if self.max > self.min {
+            ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+// Parameter values by name, stored on disk as a flat json object
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default, Debug)]
+#[serde(transparent)]
+pub struct Params {
+    values: BTreeMap<String, f32>,
+}
+
+impl Params {
+    pub fn defaults(specs: &[ParamSpec]) -> Self {
+        let mut params = Params::default();
+        specs.iter().for_each(|spec| params.set(spec.name, spec.default));
+        params
+    }
+
+    // Values missing from the set fall back to the default, all values are kept in range
+    pub fn get(&self, spec: &ParamSpec) -> f32 {
+        self.values
+            .get(spec.name)
+            .copied()
+            .unwrap_or(spec.default)
+            .clamp(spec.min, spec.max)
+    }
+
+    pub fn set(&mut self, name: &str, value: f32) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, f32)> {
+        self.values.iter().map(|(name, value)| (name.as_str(), *value))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("unable to read parameter file {}", path.display()))?;
+        serde_json::from_str(&text)
+            .with_context(|| format!("unable to parse parameter file {}", path.display()))
+    }
+
+    // Defaults only when there is no file yet, a file that cannot be read or parsed is an error
+    pub fn load_or_default<P: AsRef<Path>>(path: P, specs: &[ParamSpec]) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        if std::fs::metadata(path).is_err_and(|e| e.kind() == std::io::ErrorKind::NotFound) {
+            return Ok(Self::defaults(specs));
+        }
+        Self::load(path)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("unable to create directory {}", dir.display()))?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("unable to write parameter file {}", path.display()))
+    }
+}
+
+// A named strategy that offline tools can build from a set of parameters
+pub struct StrategyDef {
+    pub name: &'static str,
+    pub params: &'static [ParamSpec],
+    pub build: fn(&Params) -> Strategy,
+}
+
+impl StrategyDef {
+    pub fn default_params(&self) -> Params {
+        Params::defaults(self.params)
+    }
+
+    // where the tuner writes, and the bot reads, the parameters of this strategy
+    pub fn params_path(&self) -> String {
+        format!("params/{}.json", self.name)
+    }
+}
@@ -0,0 +1,224 @@
+#![allow(dead_code)]
+
+// Paths around opponents. Every opponent is a disc inflated by its radius and pickup reach,
+// stretched along where it is heading over the next `predict_ticks`. A* runs on a grid over the
+// field, where cells inside a disc are expensive rather than forbidden so that a player starting
+// next to an opponent still gets a way out, and the cell path is then shortened to the waypoints
+// that cannot see each other. `PathFollower` walks a player along the waypoints tick by tick and
+// replans as the opponents move.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use crate::core::*;
+use super::influence::Grid;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PathConfig {
+    // side of a grid cell
+    pub cell: f32,
+    // ticks opponents are projected forward along their heading
+    pub predict_ticks: f32,
+    // cost of crossing an inflated opponent, per unit of distance on top of the distance itself
+    pub obstacle_cost: f32,
+    // the follower replans at least this often
+    pub replan_ticks: u32,
+    // a waypoint this close counts as reached
+    pub arrive_radius: f32,
+}
+
+impl Default for PathConfig {
+    fn default() -> Self {
+        Self {
+            cell: 20.0,
+            predict_ticks: 10.0,
+            obstacle_cost: 20.0,
+            replan_ticks: 10,
+            arrive_radius: 15.0,
+        }
+    }
+}
+
+// An opponent swept from where it is to where it is heading
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Obstacle {
+    pub from: Vec2,
+    pub to: Vec2,
+    pub radius: f32,
+}
+
+impl Obstacle {
+    pub fn dist(&self, pos: Vec2) -> f32 {
+        Segment::new(self.from, self.to).dist(pos) - self.radius
+    }
+
+    pub fn contains(&self, pos: Vec2) -> bool {
+        self.dist(pos) < 0.0
+    }
+}
+
+// The players of the other team than `player`, inflated and projected forward
+pub fn opponent_obstacles(state: &GameState, player: PlayerId, config: &PathConfig) -> Vec<Obstacle> {
+    let team = state.player_team(player).unwrap_or(Team::Me);
+    state.players[team.other()]
+        .iter()
+        .map(|p| Obstacle {
+            from: p.pos,
+            to: p.pos + p.dir.normalize_or_zero() * (p.speed * config.predict_ticks),
+            radius: p.radius + p.pickup_radius,
+        })
+        .collect()
+}
+
+// Open set entry, ordered so that the max-heap pops the lowest estimate first
+struct Open(f32, (usize, usize));
+
+impl PartialEq for Open {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Open {}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Open {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0)
+    }
+}
+
+// Whether the straight line between two points stays out of every obstacle not containing `from`
+fn visible(from: Vec2, to: Vec2, obstacles: &[Obstacle], step: f32) -> bool {
+    let steps = (from.dist(&to) / step.max(1.0)).ceil().max(1.0) as usize;
+    obstacles.iter().filter(|o| !o.contains(from)).all(|o| {
+        (0..=steps).all(|i| !o.contains(from + (to - from) * (i as f32 / steps as f32)))
+    })
+}
+
+// Drops every waypoint the one before it can see past
+fn shorten(points: &[Vec2], obstacles: &[Obstacle], step: f32) -> Vec<Vec2> {
+    let mut path = vec![points[0]];
+    let mut anchor = 0;
+    while anchor + 1 < points.len() {
+        let next = (anchor + 1..points.len())
+            .rev()
+            .find(|&i| visible(points[anchor], points[i], obstacles, step))
+            .unwrap_or(anchor + 1);
+        path.push(points[next]);
+        anchor = next;
+    }
+    path
+}
+
+// Waypoints from `from` to `to`, both included
+pub fn find_path(from: Vec2, to: Vec2, obstacles: &[Obstacle], conf: &GameConfig, config: &PathConfig) -> Vec<Vec2> {
+    if visible(from, to, obstacles, config.cell * 0.5) {
+        return vec![from, to];
+    }
+    let mut penalty = Grid::new(&conf.field, config.cell, 0.0f32);
+    for row in 0..penalty.rows {
+        for col in 0..penalty.cols {
+            let center = penalty.center(col, row);
+            if obstacles.iter().any(|o| o.contains(center)) {
+                penalty[(col, row)] = config.obstacle_cost;
+            }
+        }
+    }
+    let (start, goal) = (penalty.nearest_cell(from), penalty.nearest_cell(to));
+    let center = |(col, row): (usize, usize)| penalty.center(col, row);
+
+    let mut cost = Grid::new(&conf.field, config.cell, f32::INFINITY);
+    let mut came_from: Grid<Option<(usize, usize)>> = Grid::new(&conf.field, config.cell, None);
+    let mut open = BinaryHeap::new();
+    cost[start] = 0.0;
+    open.push(Open(center(start).dist(&to), start));
+    while let Some(Open(_, current)) = open.pop() {
+        if current == goal {
+            break;
+        }
+        let here = center(current);
+        for next in penalty.neighbours(current.0, current.1) {
+            let there = center(next);
+            let new_cost = cost[current] + here.dist(&there) * (1.0 + penalty[next]);
+            if new_cost < cost[next] {
+                cost[next] = new_cost;
+                came_from[next] = Some(current);
+                open.push(Open(new_cost + there.dist(&to), next));
+            }
+        }
+    }
+
+    let mut cells = vec![goal];
+    while let Some(&last) = cells.last() {
+        match came_from[last] {
+            Some(prev) if last != start => cells.push(prev),
+            _ => break,
+        }
+    }
+    let points: Vec<Vec2> = std::iter::once(from)
+        .chain(cells.iter().rev().skip(1).take(cells.len().saturating_sub(2)).map(|&cell| center(cell)))
+        .chain(std::iter::once(to))
+        .collect();
+    shorten(&points, obstacles, config.cell * 0.5)
+}
+
+// Keeps a path to a target for one player and turns it into a direction every tick
+pub struct PathFollower {
+    pub config: PathConfig,
+    path: Vec<Vec2>,
+    next: usize,
+    target: Vec2,
+    planned_at: Option<u32>,
+}
+
+impl PathFollower {
+    pub fn new(config: PathConfig) -> Self {
+        Self { config, path: Vec::new(), next: 0, target: Vec2::ZERO, planned_at: None }
+    }
+
+    pub fn path(&self) -> &[Vec2] {
+        &self.path
+    }
+
+    // drop the path, the next call plans a new one
+    pub fn clear(&mut self) {
+        self.planned_at = None;
+    }
+
+    fn needs_replan(&self, tick: u32, target: Vec2) -> bool {
+        match self.planned_at {
+            Some(at) => tick < at || tick - at >= self.config.replan_ticks || target.dist(&self.target) > self.config.cell,
+            None => true,
+        }
+    }
+
+    pub fn dir(&mut self, state: &GameState, conf: &GameConfig, id: PlayerId, target: Vec2) -> Vec2 {
+        let pos = state.players[id as usize].pos;
+        if self.needs_replan(state.tick, target) {
+            let obstacles = opponent_obstacles(state, id, &self.config);
+            self.path = find_path(pos, target, &obstacles, conf, &self.config);
+            self.next = 1.min(self.path.len() - 1);
+            self.target = target;
+            self.planned_at = Some(state.tick);
+        }
+        while self.next + 1 < self.path.len() && pos.dist(&self.path[self.next]) <= self.config.arrive_radius {
+            self.next += 1;
+        }
+        self.path[self.next] - pos
+    }
+
+    pub fn action(&mut self, state: &GameState, conf: &GameConfig, id: PlayerId, target: Vec2) -> PlayerAction {
+        PlayerAction { dir: self.dir(state, conf, id, target), pass: no_pass() }
+    }
+}
+
+impl Default for PathFollower {
+    fn default() -> Self {
+        Self::new(PathConfig::default())
+    }
+}
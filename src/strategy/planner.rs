@@ -0,0 +1,209 @@
+#![allow(dead_code)]
+
+// Rollout planning on top of `core::sim`: every candidate team action is held for a few ticks
+// against sampled opponent actions, and the candidates that look best get more rollouts (UCB1).
+
+use rand::{ Rng, SeedableRng, rngs::StdRng };
+use std::time::{ Duration, Instant };
+use crate::core::*;
+use crate::core::sim::{ Simulator, StepEvent };
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PlannerConfig {
+    // ticks simulated per rollout
+    pub horizon: u32,
+    pub max_rollouts: u32,
+    // stops early once exceeded, results are only reproducible when this never triggers
+    pub time_budget: Option<Duration>,
+    // UCB1 exploration weight, in units of the evaluation
+    pub exploration: f32,
+    pub seed: u64,
+}
+
+impl Default for PlannerConfig {
+    fn default() -> Self {
+        Self {
+            horizon: 20,
+            max_rollouts: 64,
+            time_budget: None,
+            exploration: 1.0,
+            seed: 0,
+        }
+    }
+}
+
+// Produces the opponents' actions for one tick, from our point of view
+pub type OpponentSampler<'a> = dyn FnMut(&GameState, &GameConfig, &mut StdRng) -> TeamAction + 'a;
+// Scores the end of a rollout given the state it started from, higher is better for us
+pub type Evaluation<'a> = dyn Fn(&GameState, &GameState, &GameConfig) -> f32 + 'a;
+
+// Every opponent runs at the ball with probability `chase`, otherwise in a random direction.
+// Carriers shoot at our goal.
+pub fn sample_chasing_opponents(chase: f32) -> impl FnMut(&GameState, &GameConfig, &mut StdRng) -> TeamAction {
+    move |state, conf, rng| {
+        std::array::from_fn(|i| {
+            let player = &state.players[NUM_PLAYERS as usize + i];
+            let dir = if rng.random::<f32>() < chase {
+                state.ball.pos - player.pos
+            } else {
+                Vec2::from_angle_rad(rng.random_range(0.0..2.0 * PI))
+            };
+            let shoot = state.ball_owner() == Some(player.id);
+            PlayerAction {
+                dir,
+                pass: if shoot { pass(conf.field.goal_self() - player.pos) } else { no_pass() },
+            }
+        })
+    }
+}
+
+// Goals dominate, then possession, then how far up the field the ball is
+pub fn default_evaluation(start: &GameState, end: &GameState, conf: &GameConfig) -> f32 {
+    let goals = (end.score.me as f32 - start.score.me as f32) - (end.score.other as f32 - start.score.other as f32);
+    let possession = match end.ball_possession {
+        BallPossessionState::Possessed { team: Team::Me, .. } => 1.0,
+        BallPossessionState::Possessed { team: Team::Other, .. } => -1.0,
+        _ => 0.0,
+    };
+    let progress = (end.ball.pos.x - start.ball.pos.x) / conf.field.width as f32;
+    goals * 10.0 + possession + progress * 2.0
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Plan {
+    pub action: TeamAction,
+    pub index: usize,
+    // mean evaluation of the chosen candidate
+    pub value: f32,
+    pub rollouts: u32,
+}
+
+pub struct Planner {
+    pub config: PlannerConfig,
+    rng: StdRng,
+}
+
+impl Planner {
+    pub fn new(config: PlannerConfig) -> Self {
+        Self { rng: StdRng::seed_from_u64(config.seed), config }
+    }
+
+    fn rollout(
+        &mut self,
+        conf: &GameConfig,
+        state: &GameState,
+        action: &TeamAction,
+        opponents: &mut OpponentSampler,
+        evaluate: &Evaluation,
+    ) -> f32 {
+        let mut sim = Simulator::from_state(conf, state.clone(), self.rng.random());
+        for _ in 0..self.config.horizon {
+            let other = opponents(sim.state(), conf, &mut self.rng);
+            // a goal or reset ends the rollout, the score already tells what happened
+            if sim.step(&TeamPair::new(action.clone(), other)) != StepEvent::None {
+                break;
+            }
+        }
+        evaluate(state, sim.state(), conf)
+    }
+
+    // Returns `None` without candidates
+    pub fn plan(
+        &mut self,
+        conf: &GameConfig,
+        state: &GameState,
+        candidates: &[TeamAction],
+        opponents: &mut OpponentSampler,
+        evaluate: &Evaluation,
+    ) -> Option<Plan> {
+        if candidates.is_empty() {
+            return None;
+        }
+        let start = Instant::now();
+        let mut totals = vec![0.0f32; candidates.len()];
+        let mut counts = vec![0u32; candidates.len()];
+        let mut rollouts = 0;
+
+        while rollouts < self.config.max_rollouts.max(candidates.len() as u32) {
+            if self.config.time_budget.is_some_and(|budget| start.elapsed() >= budget) && rollouts > 0 {
+                break;
+            }
+            // every candidate is tried once before UCB1 picks
+            let index = match counts.iter().position(|&n| n == 0) {
+                Some(index) => index,
+                None => {
+                    let ln_n = (rollouts as f32).ln();
+                    (0..candidates.len())
+                        .max_by(|&a, &b| {
+                            let ucb = |i: usize| {
+                                totals[i] / counts[i] as f32
+                                    + self.config.exploration * (ln_n / counts[i] as f32).sqrt()
+                            };
+                            ucb(a).total_cmp(&ucb(b))
+                        })
+                        .unwrap()
+                },
+            };
+            totals[index] += self.rollout(conf, state, &candidates[index], opponents, evaluate);
+            counts[index] += 1;
+            rollouts += 1;
+        }
+
+        // the most visited candidate is the most trustworthy one
+        let index = (0..candidates.len())
+            .filter(|&i| counts[i] > 0)
+            .max_by(|&a, &b| {
+                counts[a]
+                    .cmp(&counts[b])
+                    .then((totals[a] / counts[a] as f32).total_cmp(&(totals[b] / counts[b] as f32)))
+            })
+            .unwrap();
+        Some(Plan {
+            action: candidates[index].clone(),
+            index,
+            value: totals[index] / counts[index] as f32,
+            rollouts,
+        })
+    }
+}
+
+// Simple candidate set: the ball carrier (if it is ours) either dribbles in one of `directions`
+// directions, shoots at goal, or passes to a teammate, while everyone else pushes up the field.
+// Without the ball, the `n` players nearest to it press and the rest hold, for n in 1..=all.
+pub fn basic_candidates(state: &GameState, conf: &GameConfig, directions: usize) -> Vec<TeamAction> {
+    let ball = state.ball.pos;
+    let toward = |id: usize, target: Vec2| PlayerAction { dir: target - state.players[id].pos, pass: no_pass() };
+    let mut candidates = Vec::new();
+
+    if let Some(owner) = state.ball_owner().filter(|&id| id < NUM_PLAYERS) {
+        let owner = owner as usize;
+        let support: TeamAction = std::array::from_fn(|id| toward(id, conf.field.goal_other()));
+        for i in 0..directions {
+            let mut action = support.clone();
+            action[owner].dir = Vec2::from_angle_rad(2.0 * PI * i as f32 / directions as f32);
+            candidates.push(action);
+        }
+        let mut shot = support.clone();
+        shot[owner].pass = pass(conf.field.goal_other() - state.players[owner].pos);
+        candidates.push(shot);
+        for mate in (0..NUM_PLAYERS as usize).filter(|&id| id != owner) {
+            let mut action = support.clone();
+            action[owner].pass = pass(state.players[mate].pos - state.players[owner].pos);
+            candidates.push(action);
+        }
+        return candidates;
+    }
+
+    let mut by_ball: Vec<usize> = (0..NUM_PLAYERS as usize).collect();
+    by_ball.sort_by(|&a, &b| state.players[a].pos.dist_sq(&ball).total_cmp(&state.players[b].pos.dist_sq(&ball)));
+    for pressing in 1..=NUM_PLAYERS as usize {
+        candidates.push(std::array::from_fn(|id| {
+            if by_ball[..pressing].contains(&id) {
+                toward(id, ball)
+            } else {
+                PlayerAction::default()
+            }
+        }));
+    }
+    candidates
+}
@@ -0,0 +1,264 @@
+#![allow(dead_code)]
+
+use serde::{ Serialize, Deserialize };
+use crate::core::*;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Role {
+    Goalkeeper,
+    Defender(u8),
+    Midfielder(u8),
+    Attacker(u8),
+}
+
+// Optimal assignment for a square cost matrix (Hungarian algorithm, O(n^3)).
+// Returns the column picked for every row. NaN and infinite costs would keep the potentials from
+// ever settling, so they are replaced with a cost worse (or better, for -inf) than any
+// combination of the finite ones.
+pub fn hungarian(cost: &[Vec<f32>]) -> Vec<usize> {
+    let n = cost.len();
+    let largest = cost.iter().flatten().filter(|c| c.is_finite()).fold(0.0f32, |acc, c| acc.max(c.abs()));
+    let big = (largest + 1.0) * (n as f32 + 1.0) * 2.0;
+    let cost: Vec<Vec<f32>> = cost
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|&c| match c {
+                    c if c.is_finite() => c,
+                    c if c == f32::NEG_INFINITY => -big,
+                    _ => big,
+                })
+                .collect()
+        })
+        .collect();
+    // potentials and matching are 1-indexed, index 0 is a virtual column
+    let mut u = vec![0.0f32; n + 1];
+    let mut v = vec![0.0f32; n + 1];
+    let mut row_of = vec![0usize; n + 1];
+    let mut way = vec![0usize; n + 1];
+
+    for row in 1..=n {
+        row_of[0] = row;
+        let mut col = 0;
+        let mut min_v = vec![f32::INFINITY; n + 1];
+        let mut used = vec![false; n + 1];
+        loop {
+            used[col] = true;
+            let r = row_of[col];
+            let mut delta = f32::INFINITY;
+            let mut next = 0;
+            for j in 1..=n {
+                if used[j] {
+                    continue;
+                }
+                let reduced = cost[r - 1][j - 1] - u[r] - v[j];
+                if reduced < min_v[j] {
+                    min_v[j] = reduced;
+                    way[j] = col;
+                }
+                if min_v[j] < delta {
+                    delta = min_v[j];
+                    next = j;
+                }
+            }
+            for j in 0..=n {
+                if used[j] {
+                    u[row_of[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min_v[j] -= delta;
+                }
+            }
+            col = next;
+            if row_of[col] == 0 {
+                break;
+            }
+        }
+        loop {
+            let prev = way[col];
+            row_of[col] = row_of[prev];
+            col = prev;
+            if col == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut assignment = vec![0; n];
+    for j in 1..=n {
+        if row_of[j] > 0 {
+            assignment[row_of[j] - 1] = j - 1;
+        }
+    }
+    assignment
+}
+
+// Keeps a one to one mapping between our players and a fixed set of roles, re-solved every tick.
+// A new assignment is only adopted once it is cheaper than the current one by `hysteresis`,
+// so roles do not flicker between players that are about equally suited.
+#[derive(Clone, Debug)]
+pub struct RoleAssigner {
+    roles: [Role; NUM_PLAYERS as usize],
+    // index into `roles` for every player
+    assignment: Option<[usize; NUM_PLAYERS as usize]>,
+    pub hysteresis: f32,
+}
+
+impl RoleAssigner {
+    pub fn new(roles: [Role; NUM_PLAYERS as usize], hysteresis: f32) -> Self {
+        Self { roles, assignment: None, hysteresis }
+    }
+
+    pub fn roles(&self) -> &[Role; NUM_PLAYERS as usize] {
+        &self.roles
+    }
+
+    // forget the current assignment, e.g. after a reset
+    pub fn clear(&mut self) {
+        self.assignment = None;
+    }
+
+    // `cost(id, role)` is how bad it is for our player `id` to take `role`
+    pub fn assign(&mut self, cost: impl Fn(PlayerId, Role) -> f32) {
+        let matrix: Vec<Vec<f32>> = (0..NUM_PLAYERS)
+            .map(|id| self.roles.iter().map(|&role| cost(id, role)).collect())
+            .collect();
+        let total = |assignment: &[usize]| -> f32 {
+            assignment.iter().enumerate().map(|(id, &role)| matrix[id][role]).sum()
+        };
+
+        let best = hungarian(&matrix);
+        let switch = match &self.assignment {
+            Some(current) => total(current) - total(&best) > self.hysteresis,
+            None => true,
+        };
+        if switch {
+            self.assignment = Some(std::array::from_fn(|id| best[id]));
+        }
+    }
+
+    // Assigns roles by distance between players and the target position of every role
+    pub fn assign_by_distance(&mut self, state: &GameState, target: impl Fn(Role) -> Vec2) {
+        let targets: Vec<(Role, Vec2)> = self.roles.iter().map(|&role| (role, target(role))).collect();
+        self.assign(|id, role| {
+            let (_, pos) = targets.iter().find(|(it, _)| *it == role).unwrap();
+            state.players[id as usize].pos.dist(pos)
+        });
+    }
+
+    pub fn role_of(&self, id: PlayerId) -> Option<Role> {
+        let assignment = self.assignment.as_ref()?;
+        assignment.get(id as usize).map(|&role| self.roles[role])
+    }
+
+    pub fn player_for(&self, role: Role) -> Option<PlayerId> {
+        let assignment = self.assignment.as_ref()?;
+        let index = self.roles.iter().position(|&it| it == role)?;
+        assignment.iter().position(|&it| it == index).map(|id| id as PlayerId)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{ Rng, SeedableRng, rngs::StdRng };
+
+    fn permutations(n: usize) -> Vec<Vec<usize>> {
+        if n == 0 {
+            return vec![vec![]];
+        }
+        permutations(n - 1)
+            .into_iter()
+            .flat_map(|perm| {
+                (0..n).map(move |at| {
+                    let mut perm = perm.clone();
+                    perm.insert(at, n - 1);
+                    perm
+                })
+            })
+            .collect()
+    }
+
+    fn total(cost: &[Vec<f32>], assignment: &[usize]) -> f32 {
+        assignment.iter().enumerate().map(|(row, &col)| cost[row][col]).sum()
+    }
+
+    fn is_permutation(assignment: &[usize]) -> bool {
+        let mut seen = vec![false; assignment.len()];
+        assignment.iter().all(|&col| col < seen.len() && !std::mem::replace(&mut seen[col], true))
+    }
+
+    fn brute_force(cost: &[Vec<f32>]) -> f32 {
+        permutations(cost.len()).iter().map(|perm| total(cost, perm)).fold(f32::INFINITY, f32::min)
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..200 {
+            let n = rng.random_range(1..=6);
+            let cost: Vec<Vec<f32>> = (0..n).map(|_| (0..n).map(|_| rng.random_range(-100.0..100.0)).collect()).collect();
+            let assignment = hungarian(&cost);
+            assert!(is_permutation(&assignment), "{assignment:?}");
+            let (found, best) = (total(&cost, &assignment), brute_force(&cost));
+            assert!((found - best).abs() < 1e-2, "{cost:?}: {found} instead of {best}");
+        }
+    }
+
+    #[test]
+    fn handles_non_finite_costs() {
+        let (nan, inf) = (f32::NAN, f32::INFINITY);
+        let cost = vec![
+            vec![nan, 3.0, inf, 1.0],
+            vec![2.0, nan, 5.0, inf],
+            vec![inf, 4.0, 3.0, nan],
+            vec![1.0, f32::NEG_INFINITY, 2.0, nan],
+        ];
+        let assignment = hungarian(&cost);
+        assert!(is_permutation(&assignment), "{assignment:?}");
+        // the -inf entry is taken and no bad one is
+        assert_eq!(assignment[3], 1);
+        assert!(assignment.iter().enumerate().all(|(row, &col)| cost[row][col].is_finite() || (row, col) == (3, 1)));
+
+        // same as brute force once the bad entries are swapped for large finite ones
+        let sanitized: Vec<Vec<f32>> = cost
+            .iter()
+            .map(|row| row.iter().map(|&c| if c.is_nan() || c == inf { 1e4 } else if c.is_infinite() { -1e4 } else { c }).collect())
+            .collect();
+        assert_eq!(total(&sanitized, &assignment), brute_force(&sanitized));
+
+        // a row of nothing but NaN still gets a column
+        let assignment = hungarian(&[vec![nan, nan], vec![1.0, 2.0]]);
+        assert_eq!(assignment, vec![1, 0]);
+    }
+
+    #[test]
+    fn hysteresis_keeps_assignment_for_small_gains() {
+        let roles = [Role::Goalkeeper, Role::Defender(0), Role::Midfielder(0), Role::Attacker(0)];
+        let index = |role: Role| roles.iter().position(|&it| it == role).unwrap();
+        let mut assigner = RoleAssigner::new(roles, 10.0);
+
+        // everyone takes the role at their own index
+        assigner.assign(|id, role| if index(role) == id as usize { 0.0 } else { 20.0 });
+        assert_eq!(assigner.role_of(0), Some(Role::Goalkeeper));
+        assert_eq!(assigner.role_of(1), Some(Role::Defender(0)));
+
+        // swapping the first two would save 6, under the hysteresis
+        let swapped = |gain: f32| {
+            move |id: PlayerId, role: Role| match (id, index(role)) {
+                (0, 1) | (1, 0) => 0.0,
+                (0, 0) | (1, 1) => gain * 0.5,
+                (id, i) if id as usize == i => 0.0,
+                _ => 20.0,
+            }
+        };
+        assigner.assign(swapped(6.0));
+        assert_eq!(assigner.role_of(0), Some(Role::Goalkeeper));
+        assert_eq!(assigner.player_for(Role::Defender(0)), Some(1));
+
+        // saving 12 is enough
+        assigner.assign(swapped(12.0));
+        assert_eq!(assigner.role_of(0), Some(Role::Defender(0)));
+        assert_eq!(assigner.player_for(Role::Goalkeeper), Some(1));
+    }
+}
@@ -0,0 +1,265 @@
+#![allow(dead_code)]
+
+// Scripted kickoffs. Every reset puts the ball free on the center spot, and a routine says where
+// our players line up and what each of them does over the first ticks, step by step. Routines
+// are picked in `on_reset` by how the restart came about, which the score delta since the last
+// reset tells: we conceded, we scored, or neither (start of the match, stagnation, endgame).
+// A routine gives way to normal play once it runs out of steps, and as soon as the game does not
+// go as scripted: a step takes too long, the ball ends up with the wrong player, or the
+// opponents get it.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use crate::core::*;
+use super::formations::to_field;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Restart {
+    Conceded,
+    Scored,
+    // no goal since the last reset
+    Neutral,
+}
+
+impl Restart {
+    pub fn from_scores(last: Option<&TeamPair<u32>>, score: &TeamPair<u32>) -> Self {
+        match last {
+            Some(last) if score.other > last.other && score.me >= last.me => Restart::Conceded,
+            Some(last) if score.me > last.me && score.other >= last.other => Restart::Scored,
+            _ => Restart::Neutral,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Cue {
+    // whatever normal play says
+    Free,
+    Hold,
+    // normalized field coordinates
+    MoveTo(Vec2),
+    ChaseBall,
+    // pass to a teammate once we have the ball, go for it before that
+    PassTo(PlayerId),
+    // wait for a pass and go for the ball once it is on its way
+    Receive,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StepDone {
+    // after `max_ticks`
+    Elapsed,
+    // once this player has the ball
+    Owner(PlayerId),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RoutineStep {
+    pub cues: [Cue; NUM_PLAYERS as usize],
+    pub done: StepDone,
+    // the routine aborts when a step other than `Elapsed` is not done by then
+    pub max_ticks: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Routine {
+    pub name: &'static str,
+    pub restarts: &'static [Restart],
+    // kickoff positions, these have to stay on our half (x < 0.5)
+    pub slots: [Vec2; NUM_PLAYERS as usize],
+    pub steps: &'static [RoutineStep],
+}
+
+// The striker wins the ball and switches it to the left wing, who is already running up the field
+pub const SWITCH_PLAY: Routine = Routine {
+    name: "switch_play",
+    restarts: &[Restart::Conceded, Restart::Neutral],
+    slots: [
+        Vec2 { x: 0.08, y: 0.5 },
+        Vec2 { x: 0.25, y: 0.5 },
+        Vec2 { x: 0.4, y: 0.2 },
+        Vec2 { x: 0.45, y: 0.5 },
+    ],
+    steps: &[
+        RoutineStep {
+            cues: [Cue::Free, Cue::Hold, Cue::MoveTo(Vec2 { x: 0.55, y: 0.2 }), Cue::ChaseBall],
+            done: StepDone::Owner(3),
+            max_ticks: 30,
+        },
+        RoutineStep {
+            cues: [Cue::Free, Cue::Hold, Cue::Receive, Cue::PassTo(2)],
+            done: StepDone::Owner(2),
+            max_ticks: 40,
+        },
+    ],
+};
+
+// After scoring the opponents come for the ball, so the striker wins it and lays it back while
+// the others stay compact
+pub const LAY_BACK: Routine = Routine {
+    name: "lay_back",
+    restarts: &[Restart::Scored],
+    slots: [
+        Vec2 { x: 0.08, y: 0.5 },
+        Vec2 { x: 0.3, y: 0.6 },
+        Vec2 { x: 0.3, y: 0.35 },
+        Vec2 { x: 0.45, y: 0.5 },
+    ],
+    steps: &[
+        RoutineStep {
+            cues: [Cue::Free, Cue::Hold, Cue::Hold, Cue::ChaseBall],
+            done: StepDone::Owner(3),
+            max_ticks: 30,
+        },
+        RoutineStep {
+            cues: [Cue::Free, Cue::Receive, Cue::Hold, Cue::PassTo(1)],
+            done: StepDone::Owner(1),
+            max_ticks: 40,
+        },
+    ],
+};
+
+pub const ROUTINES: &[Routine] = &[SWITCH_PLAY, LAY_BACK];
+
+pub fn find_routine(name: &str) -> Option<&'static Routine> {
+    ROUTINES.iter().find(|it| it.name == name)
+}
+
+struct Active {
+    routine: &'static Routine,
+    step: usize,
+    // tick the current step started at, known from the first tick after the reset on
+    step_start: Option<u32>,
+}
+
+pub struct SetPieces {
+    routines: Vec<&'static Routine>,
+    last_score: Option<TeamPair<u32>>,
+    active: Option<Active>,
+    // print when routines start and abort
+    pub debug: bool,
+}
+
+impl SetPieces {
+    pub fn new(routines: &[&'static Routine]) -> Self {
+        Self { routines: routines.to_vec(), last_score: None, active: None, debug: false }
+    }
+
+    pub fn active_routine(&self) -> Option<&'static Routine> {
+        self.active.as_ref().map(|active| active.routine)
+    }
+
+    // Picks the first routine for this restart, its kickoff positions if there is one
+    pub fn on_reset(&mut self, score: &TeamPair<u32>, conf: &GameConfig) -> Option<[Vec2; NUM_PLAYERS as usize]> {
+        let restart = Restart::from_scores(self.last_score.as_ref(), score);
+        self.last_score = Some(*score);
+        let routine = self.routines.iter().find(|routine| routine.restarts.contains(&restart)).copied();
+        self.active = routine.map(|routine| Active { routine, step: 0, step_start: None });
+        if self.debug {
+            if let Some(routine) = routine {
+                println!("set piece {} after {:?}", routine.name, restart);
+            }
+        }
+        routine.map(|routine| routine.slots.map(|slot| to_field(slot, &conf.field)))
+    }
+
+    fn abort(&mut self, tick: u32, why: &str) {
+        if self.debug {
+            if let Some(active) = &self.active {
+                println!("set piece {} aborted at tick {} in step {}: {}", active.routine.name, tick, active.step, why);
+            }
+        }
+        self.active = None;
+    }
+
+    // Moves the routine along and returns the cues of its current step, none once normal play
+    // has taken over
+    fn cues(&mut self, state: &GameState) -> Option<[Cue; NUM_PLAYERS as usize]> {
+        let owner = state.ball_owner();
+        loop {
+            let active = self.active.as_mut()?;
+            let Some(step) = active.routine.steps.get(active.step) else {
+                self.active = None;
+                return None;
+            };
+            let start = *active.step_start.get_or_insert(state.tick);
+            let elapsed = state.tick.saturating_sub(start);
+
+            if owner.is_some_and(|id| state.player_team(id) == Some(Team::Other)) {
+                self.abort(state.tick, "the opponents have the ball");
+                return None;
+            }
+            let done = match step.done {
+                StepDone::Elapsed => elapsed >= step.max_ticks,
+                StepDone::Owner(id) => {
+                    // only the expected receiver and whoever is passing to it may have the ball
+                    let expected = |owner: PlayerId| owner == id || matches!(step.cues[owner as usize], Cue::PassTo(_));
+                    if owner.is_some_and(|owner| !expected(owner)) {
+                        self.abort(state.tick, "the ball is with the wrong player");
+                        return None;
+                    }
+                    if owner != Some(id) && elapsed >= step.max_ticks {
+                        self.abort(state.tick, "the step took too long");
+                        return None;
+                    }
+                    owner == Some(id)
+                },
+            };
+            if !done {
+                return Some(step.cues);
+            }
+            active.step += 1;
+            active.step_start = Some(state.tick);
+        }
+    }
+
+    // Overrides the scripted players in `actions`, the output of normal play
+    pub fn apply(&mut self, state: &GameState, conf: &GameConfig, actions: &mut [PlayerAction; NUM_PLAYERS as usize]) {
+        let Some(cues) = self.cues(state) else {
+            return;
+        };
+        let owner = state.ball_owner();
+        let passing = matches!(state.ball_possession, BallPossessionState::Passing { team: Team::Me });
+        for (id, cue) in cues.iter().enumerate() {
+            let pos = state.players[id].pos;
+            actions[id] = match *cue {
+                Cue::Free => continue,
+                Cue::Hold => PlayerAction { dir: Vec2::ZERO, pass: no_pass() },
+                Cue::MoveTo(slot) => PlayerAction { dir: to_field(slot, &conf.field) - pos, pass: no_pass() },
+                Cue::ChaseBall => PlayerAction { dir: state.ball.pos - pos, pass: no_pass() },
+                Cue::PassTo(to) if owner == Some(id as PlayerId) => {
+                    let dir = state.players[to as usize].pos - pos;
+                    PlayerAction { dir: Vec2::ZERO, pass: pass(dir) }
+                },
+                Cue::PassTo(_) if owner.is_none() => PlayerAction { dir: state.ball.pos - pos, pass: no_pass() },
+                Cue::PassTo(_) => PlayerAction { dir: Vec2::ZERO, pass: no_pass() },
+                Cue::Receive if passing => PlayerAction { dir: state.ball.pos - pos, pass: no_pass() },
+                Cue::Receive => PlayerAction { dir: Vec2::ZERO, pass: no_pass() },
+            };
+        }
+    }
+
+    // Runs on top of `normal`, which still gets every reset and tick
+    pub fn into_strategy(self, normal: Strategy) -> Strategy {
+        let set_pieces = Rc::new(RefCell::new(self));
+        let on_reset_pieces = set_pieces.clone();
+        let Strategy { on_reset, on_tick } = normal;
+        Strategy {
+            on_reset: Box::new(move |score| {
+                let positions = on_reset(score);
+                on_reset_pieces.borrow_mut().on_reset(score, get_config()).unwrap_or(positions)
+            }),
+            on_tick: Box::new(move |state, history| {
+                let mut actions = on_tick(state, history);
+                set_pieces.borrow_mut().apply(state, get_config(), &mut actions);
+                actions
+            }),
+        }
+    }
+}
+
+impl Default for SetPieces {
+    fn default() -> Self {
+        Self::new(&ROUTINES.iter().collect::<Vec<_>>())
+    }
+}
@@ -0,0 +1,107 @@
+#![allow(dead_code)]
+
+// Shot selection for the ball carrier: aim points are spread across the mouth of the other goal,
+// and every one is scored by how many of the directions the pass error can send the ball in
+// reach the goal before friction stops it or a player gets to it.
+
+use crate::core::*;
+use super::endgame::open_segments;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ShotConfig {
+    // aim points across the goal mouth
+    pub aim_points: usize,
+    // directions tried per aim point, evenly spread over the pass error
+    pub error_samples: usize,
+}
+
+impl Default for ShotConfig {
+    fn default() -> Self {
+        Self {
+            aim_points: 15,
+            error_samples: 9,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Shot {
+    pub aim: Vec2,
+    // direction to pass in, from the ball
+    pub dir: Vec2,
+    // share of the sampled directions that score
+    pub probability: f32,
+}
+
+// The mouth of the other goal, kept a ball radius inside the posts
+pub fn aim_points(tick: u32, conf: &GameConfig, count: usize) -> Vec<Vec2> {
+    let mouth = Segment::goal_mouth_at(conf, Team::Other, tick);
+    if count <= 1 {
+        return vec![mouth.midpoint()];
+    }
+    let inset = (conf.ball.radius / mouth.length().max(EPSILON)).min(0.5);
+    let inner = Segment::new(mouth.point_at(inset), mouth.point_at(1.0 - inset));
+    (0..count).map(|i| inner.point_at(i as f32 / (count - 1) as f32)).collect()
+}
+
+// Whether a ball passed from `from` along the unit vector `dir` scores: it has to cross the goal
+// line inside the mouth before stopping, with nobody but `shooter` able to get within pickup
+// reach of it first
+fn scores(state: &GameState, conf: &GameConfig, shooter: PlayerId, from: Vec2, dir: Vec2) -> bool {
+    let mouth = Segment::goal_mouth_at(conf, Team::Other, state.tick);
+    let line = mouth.a.x;
+    if dir.x <= EPSILON || Ray::new(from, dir).and_then(|ray| ray.intersect_segment(&mouth)).is_none() {
+        return false;
+    }
+
+    let decay = conf.ball.decay();
+    let batch = PlayerBatch::new(&state.players);
+    let mut pos = from;
+    let mut speed = conf.player.pass_speed;
+    let mut tick = 0;
+    while pos.x < line {
+        if speed < EPSILON {
+            return false;
+        }
+        pos += dir * speed;
+        speed *= decay;
+        tick += 1;
+        let reached = !batch.reaching(pos, tick as f32).without(shooter).is_empty();
+        if reached && pos.x < line {
+            return false;
+        }
+    }
+    true
+}
+
+pub fn evaluate_shot(state: &GameState, conf: &GameConfig, shooter: PlayerId, aim: Vec2, config: &ShotConfig) -> Shot {
+    let from = state.ball.pos;
+    let dir = (aim - from).normalize_or_zero();
+    let samples = config.error_samples.max(1);
+    let error = conf.player.pass_error;
+    let hits = (0..samples)
+        .filter(|&i| {
+            let t = if samples == 1 { 0.5 } else { i as f32 / (samples - 1) as f32 };
+            scores(state, conf, shooter, from, dir.rotate_deg(-error + 2.0 * error * t))
+        })
+        .count();
+    Shot { aim, dir, probability: hits as f32 / samples as f32 }
+}
+
+// The aim point with the best chance, ties going to the one closest to the middle of the goal.
+// In endgame the middle of every stretch of the goal line left open by the opponents is tried too.
+pub fn best_shot(state: &GameState, conf: &GameConfig, shooter: PlayerId, config: &ShotConfig) -> Shot {
+    let center = conf.field.goal_other();
+    let mut aims = aim_points(state.tick, conf, config.aim_points);
+    if conf.is_endgame(state.tick) {
+        aims.extend(open_segments(state, conf, state.ball.pos).iter().map(|&(a, b)| Vec2::new(center.x, (a + b) * 0.5)));
+    }
+    aims.into_iter()
+        .map(|aim| evaluate_shot(state, conf, shooter, aim, config))
+        .max_by(|a, b| {
+            a.probability
+                .total_cmp(&b.probability)
+                .then(b.aim.dist(&center).total_cmp(&a.aim.dist(&center)))
+        })
+        .unwrap()
+}
@@ -0,0 +1,153 @@
+#![allow(dead_code)]
+
+// Local obstacle avoidance for our players, in the spirit of reciprocal velocity obstacles.
+// Players always move at full speed or not at all, so instead of solving for the closest safe
+// velocity every player picks among candidate headings around the one it wants (and standing
+// still) the one trading off deviation against how soon it would hit somebody. Teammates that
+// still have to steer take half of the avoidance each, like in ORCA; those that already steered
+// and opponents are expected to keep going as they are. Headings running into the field
+// boundary, where the engine clamps the move away, are penalized for the distance lost.
+
+use crate::core::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SteeringConfig {
+    // headings tried on each side of the wanted one
+    pub candidates: usize,
+    // widest deviation from the wanted heading, in degrees
+    pub max_turn: f32,
+    // collisions further away than this many ticks are ignored
+    pub horizon: f32,
+    // cost of an immediate collision, in ticks of movement at full speed
+    pub avoidance: f32,
+    // extra distance kept between players on top of their radii
+    pub margin: f32,
+    // run into the opponent carrying the ball, that is how it gets tackled
+    pub tackle_carrier: bool,
+    // also steer our carrier and whoever heads straight at the ball, they usually have to get
+    // through opponents
+    pub steer_ball_players: bool,
+}
+
+impl Default for SteeringConfig {
+    fn default() -> Self {
+        Self {
+            candidates: 12,
+            max_turn: 120.0,
+            horizon: 15.0,
+            avoidance: 3.0,
+            margin: 1.0,
+            tackle_carrier: true,
+            steer_ball_players: false,
+        }
+    }
+}
+
+// Ticks until two discs `radius` apart collide, `rel_pos` and `rel_vel` being those of the other
+// disc relative to ours. Zero when they already overlap and are not moving apart.
+pub fn time_to_collision(rel_pos: Vec2, rel_vel: Vec2, radius: f32) -> Option<f32> {
+    let c = rel_pos.norm_sq() - radius * radius;
+    let b = rel_pos.dot(rel_vel);
+    if c <= 0.0 {
+        return (b < 0.0).then_some(0.0);
+    }
+    let a = rel_vel.norm_sq();
+    if b >= 0.0 || a < EPSILON {
+        return None;
+    }
+    let disc = b * b - a * c;
+    (disc >= 0.0).then(|| (-b - disc.sqrt()) / a)
+}
+
+// Distance a move from `pos` loses to clamping at the field edges
+fn boundary_loss(pos: Vec2, vel: Vec2, radius: f32, conf: &GameConfig) -> f32 {
+    let size = conf.field.bottom_right();
+    let end = pos + vel;
+    let clamped = Vec2::new(end.x.clamp(radius, size.x - radius), end.y.clamp(radius, size.y - radius));
+    end.dist(&clamped)
+}
+
+struct Mover {
+    pos: Vec2,
+    vel: Vec2,
+    radius: f32,
+    // whether it steers itself and takes half of the avoidance
+    reciprocal: bool,
+}
+
+// Adjusts the directions of our players in `actions`, in id order. Passes are left alone.
+pub fn steer(state: &GameState, conf: &GameConfig, actions: &mut [PlayerAction; NUM_PLAYERS as usize], config: &SteeringConfig) {
+    let owner = state.ball_owner();
+    let speed_of = |id: usize| {
+        let player = &state.players[id];
+        if owner == Some(id as PlayerId) { player.speed * conf.player.possession_slowdown } else { player.speed }
+    };
+    // everybody on the field, opponents keep the heading they had last tick
+    let mut movers: Vec<Mover> = (0..NUM_PLAYERS as usize * 2)
+        .map(|id| {
+            let player = &state.players[id];
+            let ours = state.player_team(id as PlayerId) == Some(Team::Me);
+            let dir = if ours { actions[id].dir } else { player.dir };
+            Mover { pos: player.pos, vel: dir.normalize_or_zero() * speed_of(id), radius: player.radius, reciprocal: ours }
+        })
+        .collect();
+    let carrier = owner.filter(|&id| config.tackle_carrier && state.player_team(id) == Some(Team::Other));
+
+    for id in 0..NUM_PLAYERS as usize {
+        if !movers[id].reciprocal {
+            continue;
+        }
+        let wanted = movers[id].vel;
+        let to_ball = (state.ball.pos - movers[id].pos).normalize_or_zero();
+        let on_ball = owner == Some(id as PlayerId) || wanted.normalize_or_zero().dot(to_ball) > 0.99;
+        if wanted.norm_sq() < EPSILON || (on_ball && !config.steer_ball_players) {
+            movers[id].reciprocal = false;
+            continue;
+        }
+        let speed = speed_of(id);
+        let steps = config.candidates.max(1);
+        let candidates = std::iter::once(Vec2::ZERO).chain((0..=2 * steps).map(|i| {
+            let turn = config.max_turn * (i as f32 - steps as f32) / steps as f32;
+            wanted.rotate_deg(turn)
+        }));
+
+        let me = &movers[id];
+        let cost = |vel: Vec2| {
+            let mut soonest = config.horizon;
+            for (other_id, other) in movers.iter().enumerate() {
+                if other_id == id || Some(other_id as PlayerId) == carrier {
+                    continue;
+                }
+                // with reciprocity the other turns away as much as we do
+                let rel_vel = if other.reciprocal { other.vel - (vel * 2.0 - wanted) } else { other.vel - vel };
+                let radius = me.radius + other.radius + config.margin;
+                if let Some(t) = time_to_collision(other.pos - me.pos, rel_vel, radius) {
+                    soonest = soonest.min(t);
+                }
+            }
+            let danger = (1.0 - soonest / config.horizon.max(EPSILON)).max(0.0);
+            vel.dist(&wanted) + config.avoidance * speed * danger + boundary_loss(me.pos, vel, me.radius, conf)
+        };
+        let best = candidates
+            .map(|vel| (vel, cost(vel)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(wanted, |(vel, _)| vel);
+
+        actions[id].dir = best / speed.max(EPSILON);
+        movers[id].vel = best;
+        movers[id].reciprocal = false;
+    }
+}
+
+// Wraps a strategy so that every tick its moves are steered around the other players
+pub fn steered(strategy: Strategy, config: SteeringConfig) -> Strategy {
+    let on_tick = strategy.on_tick;
+    Strategy {
+        on_reset: strategy.on_reset,
+        on_tick: Box::new(move |state, history| {
+            let mut actions = on_tick(state, history);
+            steer(state, get_config(), &mut actions, &config);
+            actions
+        }),
+    }
+}
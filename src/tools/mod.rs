@@ -0,0 +1,73 @@
+#![allow(dead_code)]
+
+// Offline commands that run strategies in-process through `core::sim`
+
+pub mod tune;
+pub mod tournament;
+pub mod rollouts;
+pub mod report;
+
+use anyhow::Context;
+use std::collections::HashMap;
+use std::str::FromStr;
+use crate::strategy::find_strategy;
+use crate::strategy::params::StrategyDef;
+
+// Positional arguments followed by `--flag value` pairs
+pub struct Args {
+    positional: Vec<String>,
+    flags: HashMap<String, String>,
+}
+
+impl Args {
+    pub fn parse(args: &[String]) -> anyhow::Result<Self> {
+        let mut positional = Vec::new();
+        let mut flags = HashMap::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if let Some(flag) = arg.strip_prefix("--") {
+                let value = iter
+                    .next()
+                    .with_context(|| format!("missing value for --{}", flag))?;
+                flags.insert(flag.to_string(), value.clone());
+            } else {
+                positional.push(arg.clone());
+            }
+        }
+        Ok(Self { positional, flags })
+    }
+
+    pub fn positional(&self, index: usize, name: &str) -> anyhow::Result<&str> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .with_context(|| format!("missing argument <{}>", name))
+    }
+
+    pub fn flag_str(&self, name: &str) -> Option<&str> {
+        self.flags.get(name).map(String::as_str)
+    }
+
+    pub fn flag<T: FromStr>(&self, name: &str, default: T) -> anyhow::Result<T> {
+        match self.flags.get(name) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| anyhow::anyhow!("invalid value for --{}: {}", name, value)),
+            None => Ok(default),
+        }
+    }
+
+    // comma separated list, e.g. `--opponents a,b,c`
+    pub fn flag_list(&self, name: &str) -> Option<Vec<String>> {
+        self.flags
+            .get(name)
+            .map(|value| value.split(',').filter(|s| !s.is_empty()).map(str::to_string).collect())
+    }
+}
+
+pub fn find_strategies(names: &[String]) -> anyhow::Result<Vec<&'static StrategyDef>> {
+    names
+        .iter()
+        .map(|name| find_strategy(name).with_context(|| format!("unknown strategy {}", name)))
+        .collect()
+}
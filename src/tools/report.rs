@@ -0,0 +1,297 @@
+// `bot report`: plays (or loads) a recorded match and breaks it down beyond the final score
+
+use anyhow::Context;
+use serde::{ Serialize, Deserialize };
+use std::fmt::Write;
+use crate::core::*;
+use crate::core::events::{ EventDetector, GameEventKind };
+use crate::core::sim::Match;
+use crate::strategy::find_strategy;
+use crate::strategy::params::*;
+use super::Args;
+
+pub const USAGE: &str = "usage: [bin name] report <config.json> (--left <strategy> --right <strategy> | --replay match.json) \
+[--seed 0] [--save match.json] [--out report]";
+
+const HEATMAP_COLS: usize = 12;
+const HEATMAP_ROWS: usize = 8;
+// characters for heatmap cells in the markdown report, from empty to most visited
+const SHADES: &[u8] = b" .:-=+*#%@";
+
+// Every state of a match, from the point of view of the team on the left
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct MatchRecording {
+    pub names: TeamPair<String>,
+    pub seed: u64,
+    pub states: Vec<GameState>,
+}
+
+impl MatchRecording {
+    pub fn load(path: &str) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("unable to read recording {}", path))?;
+        serde_json::from_str(&text).with_context(|| format!("unable to parse recording {}", path))
+    }
+
+    pub fn save(&self, path: &str) -> anyhow::Result<()> {
+        std::fs::write(path, serde_json::to_string(self)?)
+            .with_context(|| format!("unable to write recording {}", path))
+    }
+}
+
+pub fn record_match(conf: &GameConfig, left: &StrategyDef, right: &StrategyDef, seed: u64) -> anyhow::Result<MatchRecording> {
+    let me = (left.build)(&Params::load_or_default(left.params_path(), left.params)?);
+    let other = (right.build)(&Params::load_or_default(right.params_path(), right.params)?);
+    let mut game = Match::new(conf, &me, &other, seed);
+    let mut states = vec![game.state().clone()];
+    while !game.is_finished() {
+        game.step();
+        states.push(game.state().clone());
+    }
+    Ok(MatchRecording {
+        names: TeamPair::new(left.name.to_string(), right.name.to_string()),
+        seed,
+        states,
+    })
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct PlayerReport {
+    pub id: PlayerId,
+    pub distance: f32,
+    // share of ticks spent in every cell, row-major, `MatchReport::heatmap_cols` wide
+    pub heatmap: Vec<f32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default, Debug)]
+pub struct TeamReport {
+    pub name: String,
+    pub goals: u32,
+    // share of the ticks either team had the ball (carried or passing)
+    pub possession: f32,
+    pub passes_attempted: u32,
+    pub passes_completed: u32,
+    // passes of the other team picked up by this one
+    pub interceptions: u32,
+    pub tackles: u32,
+    // passes heading into the other goal mouth
+    pub shots: u32,
+    pub centroid: Vec2,
+    // mean distance of the players to the centroid
+    pub spread: f32,
+    // share of ticks the ball spent in this team's defensive, middle and attacking third
+    pub thirds: [f32; 3],
+    pub players: Vec<PlayerReport>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct MatchReport {
+    pub ticks: u32,
+    pub stagnation_resets: u32,
+    pub heatmap_cols: usize,
+    pub heatmap_rows: usize,
+    pub teams: TeamPair<TeamReport>,
+}
+
+fn heatmap_cell(pos: Vec2, conf: &GameConfig) -> usize {
+    let size = conf.field.bottom_right();
+    let col = ((pos.x / size.x * HEATMAP_COLS as f32).max(0.0) as usize).min(HEATMAP_COLS - 1);
+    let row = ((pos.y / size.y * HEATMAP_ROWS as f32).max(0.0) as usize).min(HEATMAP_ROWS - 1);
+    row * HEATMAP_COLS + col
+}
+
+// Whether a ball leaving `from` along `dir` crosses the goal line of the team defending it
+fn is_shot(from: Vec2, dir: Vec2, defending: Team, tick: u32, conf: &GameConfig) -> bool {
+    let mouth = Segment::goal_mouth_at(conf, defending, tick);
+    Ray::new(from, dir).and_then(|ray| ray.intersect_segment(&mouth)).is_some_and(|t| t > 0.0)
+}
+
+pub fn analyze(recording: &MatchRecording, conf: &GameConfig) -> MatchReport {
+    let players = NUM_PLAYERS as usize * 2;
+    let mut teams = TeamPair::new(TeamReport::default(), TeamReport::default());
+    let mut heatmaps = vec![vec![0u32; HEATMAP_COLS * HEATMAP_ROWS]; players];
+    let mut distance = vec![0.0f32; players];
+    let mut held = TeamPair::new(0u32, 0u32);
+    let mut thirds = [0u32; 3];
+    let mut shape = TeamPair::new((Vec2::ZERO, 0.0f32), (Vec2::ZERO, 0.0f32));
+    let mut stagnation_resets = 0;
+    let mut detector = EventDetector::new();
+
+    for (i, state) in recording.states.iter().enumerate() {
+        for player in &state.players {
+            heatmaps[player.id as usize][heatmap_cell(player.pos, conf)] += 1;
+        }
+        if let Some(prev) = i.checked_sub(1).map(|j| &recording.states[j]) {
+            for (player, before) in state.players.iter().zip(&prev.players) {
+                let step = player.pos.dist(&before.pos);
+                // longer steps are resets
+                if step <= conf.player.speed * 1.5 + EPSILON {
+                    distance[player.id as usize] += step;
+                }
+            }
+        }
+
+        match state.ball_possession {
+            BallPossessionState::Possessed { team, .. } | BallPossessionState::Passing { team } => held[team] += 1,
+            BallPossessionState::Free => (),
+        }
+        let third = ((state.ball.pos.x / conf.field.width as f32 * 3.0).max(0.0) as usize).min(2);
+        thirds[third] += 1;
+
+        let sides = state.teams();
+        for team in [Team::Me, Team::Other] {
+            let centroid = sides[team].iter().map(|player| player.pos).sum::<Vec2>() / NUM_PLAYERS as f32;
+            let spread = sides[team].iter().map(|player| player.pos.dist(&centroid)).sum::<f32>() / NUM_PLAYERS as f32;
+            shape[team].0 += centroid;
+            shape[team].1 += spread;
+        }
+
+        for event in detector.update(state, conf) {
+            match event.kind {
+                GameEventKind::PassStarted { team, dir, .. } => {
+                    teams[team].passes_attempted += 1;
+                    if is_shot(state.ball.pos, dir, team.other(), state.tick, conf) {
+                        teams[team].shots += 1;
+                    }
+                },
+                GameEventKind::PassCompleted { team, .. } => teams[team].passes_completed += 1,
+                GameEventKind::PassIntercepted { team, .. } => teams[team.other()].interceptions += 1,
+                GameEventKind::Tackle { by, .. } => {
+                    if let Some(team) = state.player_team(by) {
+                        teams[team].tackles += 1;
+                    }
+                },
+                GameEventKind::StagnationReset => stagnation_resets += 1,
+                _ => (),
+            }
+        }
+    }
+
+    let samples = recording.states.len().max(1) as f32;
+    let last = recording.states.last();
+    let held_total = (held.me + held.other).max(1) as f32;
+    for team in [Team::Me, Team::Other] {
+        let report = &mut teams[team];
+        report.name = recording.names[team].clone();
+        report.goals = last.map_or(0, |state| state.score[team]);
+        report.possession = held[team] as f32 / held_total;
+        report.centroid = shape[team].0 / samples;
+        report.spread = shape[team].1 / samples;
+        let share = thirds.map(|n| n as f32 / samples);
+        report.thirds = match team {
+            Team::Me => share,
+            Team::Other => [share[2], share[1], share[0]],
+        };
+        let ids = match team {
+            Team::Me => 0..NUM_PLAYERS,
+            Team::Other => NUM_PLAYERS..NUM_PLAYERS * 2,
+        };
+        report.players = ids
+            .map(|id| PlayerReport {
+                id,
+                distance: distance[id as usize],
+                heatmap: heatmaps[id as usize].iter().map(|&n| n as f32 / samples).collect(),
+            })
+            .collect();
+    }
+
+    MatchReport {
+        ticks: last.map_or(0, |state| state.tick),
+        stagnation_resets,
+        heatmap_cols: HEATMAP_COLS,
+        heatmap_rows: HEATMAP_ROWS,
+        teams,
+    }
+}
+
+impl MatchReport {
+    pub fn to_markdown(&self) -> String {
+        let (me, other) = (&self.teams.me, &self.teams.other);
+        let mut out = String::new();
+        let _ = writeln!(out, "# {} {} - {} {}\n", me.name, me.goals, other.goals, other.name);
+        let _ = writeln!(out, "{} ticks, {} stagnation resets\n", self.ticks, self.stagnation_resets);
+
+        let _ = writeln!(out, "| | {} | {} |", me.name, other.name);
+        let _ = writeln!(out, "|---|---:|---:|");
+        let mut row = |label: &str, value: &dyn Fn(&TeamReport) -> String| {
+            let _ = writeln!(out, "| {} | {} | {} |", label, value(me), value(other));
+        };
+        row("goals", &|t| t.goals.to_string());
+        row("possession", &|t| format!("{:.0}%", t.possession * 100.0));
+        row("passes (completed / attempted)", &|t| format!("{} / {}", t.passes_completed, t.passes_attempted));
+        row("interceptions", &|t| t.interceptions.to_string());
+        row("tackles", &|t| t.tackles.to_string());
+        row("shots", &|t| t.shots.to_string());
+        row("centroid", &|t| format!("({:.0}, {:.0})", t.centroid.x, t.centroid.y));
+        row("spread", &|t| format!("{:.0}", t.spread));
+        row("ball in defensive / middle / attacking third", &|t| {
+            format!("{:.0}% / {:.0}% / {:.0}%", t.thirds[0] * 100.0, t.thirds[1] * 100.0, t.thirds[2] * 100.0)
+        });
+
+        for team in [me, other] {
+            let _ = writeln!(out, "\n## {}\n", team.name);
+            let _ = writeln!(out, "| player | distance |");
+            let _ = writeln!(out, "|---:|---:|");
+            for player in &team.players {
+                let _ = writeln!(out, "| {} | {:.0} |", player.id, player.distance);
+            }
+            for player in &team.players {
+                let _ = writeln!(out, "\nplayer {} heatmap (left team attacks to the right)\n", player.id);
+                let _ = writeln!(out, "```");
+                let max = player.heatmap.iter().copied().fold(0.0, f32::max).max(EPSILON);
+                for row in player.heatmap.chunks(self.heatmap_cols) {
+                    let line: String = row
+                        .iter()
+                        .map(|&share| {
+                            let level = (share / max * (SHADES.len() - 1) as f32).round() as usize;
+                            SHADES[level.min(SHADES.len() - 1)] as char
+                        })
+                        .collect();
+                    let _ = writeln!(out, "|{}|", line);
+                }
+                let _ = writeln!(out, "```");
+            }
+        }
+        out
+    }
+}
+
+pub fn run(args: &[String]) -> anyhow::Result<()> {
+    let args = Args::parse(args)?;
+    let Ok(config_path) = args.positional(0, "config.json") else {
+        println!("{}", USAGE);
+        return Ok(());
+    };
+
+    let conf = load_config(config_path)?;
+    init_config(conf.clone())?;
+
+    let recording = match (args.flag_str("replay"), args.flag_str("left"), args.flag_str("right")) {
+        (Some(path), _, _) => MatchRecording::load(path)?,
+        (None, Some(left), Some(right)) => {
+            let unknown = |name: &str| anyhow::anyhow!("unknown strategy {}", name);
+            let left = find_strategy(left).ok_or_else(|| unknown(left))?;
+            let right = find_strategy(right).ok_or_else(|| unknown(right))?;
+            record_match(&conf, left, right, args.flag("seed", 0)?)?
+        },
+        _ => {
+            println!("{}", USAGE);
+            return Ok(());
+        },
+    };
+    if let Some(path) = args.flag_str("save") {
+        recording.save(path)?;
+    }
+
+    let report = analyze(&recording, &conf);
+    let out = args.flag_str("out").unwrap_or("report");
+    let markdown = report.to_markdown();
+    std::fs::write(format!("{}.md", out), &markdown)
+        .with_context(|| format!("unable to write {}.md", out))?;
+    std::fs::write(format!("{}.json", out), serde_json::to_string_pretty(&report)?)
+        .with_context(|| format!("unable to write {}.json", out))?;
+
+    println!("{} {} - {} {}", report.teams.me.name, report.teams.me.goals, report.teams.other.goals, report.teams.other.name);
+    println!("report written to {}.md and {}.json", out, out);
+    Ok(())
+}
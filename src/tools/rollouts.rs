@@ -0,0 +1,47 @@
+// `bot rollouts`: dumps (observation, action, reward) transitions of a strategy as json lines
+
+use crate::core::*;
+use crate::learn::env::{ Env, RolloutWriter, record_rollouts };
+use crate::strategy::find_strategy;
+use super::Args;
+
+pub const USAGE: &str = "usage: [bin name] rollouts <config.json> --agent <strategy> --opponent <strategy> \
+[--episodes 4] [--seed 0] [--out rollouts.jsonl]";
+
+pub fn run(args: &[String]) -> anyhow::Result<()> {
+    let args = Args::parse(args)?;
+    let (Ok(config_path), Some(agent), Some(opponent)) = (
+        args.positional(0, "config.json"),
+        args.flag_str("agent"),
+        args.flag_str("opponent"),
+    ) else {
+        println!("{}", USAGE);
+        return Ok(());
+    };
+
+    let conf = load_config(config_path)?;
+    init_config(conf.clone())?;
+
+    let unknown = |name: &str| anyhow::anyhow!("unknown strategy {}", name);
+    let agent = find_strategy(agent).ok_or_else(|| unknown(agent))?;
+    let opponent = find_strategy(opponent).ok_or_else(|| unknown(opponent))?;
+    let episodes: u64 = args.flag("episodes", 4)?;
+    let out = args.flag_str("out").unwrap_or("rollouts.jsonl");
+
+    let mut writer = RolloutWriter::create(out)?;
+    let build = || ((agent.build)(&agent.default_params()), (opponent.build)(&opponent.default_params()));
+    let (me, other) = build();
+    let mut env = Env::new(&conf, Team::Me, me, other, args.flag("seed", 0)?);
+    // alternate sides so the data covers both, observations are mirrored either way
+    for episode in 0..episodes {
+        env.set_side(if episode % 2 == 0 { Team::Me } else { Team::Other });
+        if episode > 0 {
+            let (me, other) = build();
+            env.set_strategies(me, other);
+        }
+        record_rollouts(&mut env, 1, &mut writer)?;
+    }
+
+    println!("{} episodes written to {}", episodes, out);
+    Ok(())
+}
@@ -0,0 +1,201 @@
+// `bot tournament`: round robin between registered strategies, with a crosstable and Elo ratings
+
+use anyhow::Context;
+use rand::{ SeedableRng, rngs::StdRng, seq::SliceRandom };
+use serde::{ Serialize, Deserialize };
+use std::fmt::Write;
+use crate::core::*;
+use crate::core::sim::{ MatchResult, play_match };
+use crate::strategy::STRATEGIES;
+use crate::strategy::params::*;
+use super::{ Args, find_strategies };
+
+pub const USAGE: &str = "usage: [bin name] tournament <config.json> [--strategies a,b,c] [--seeds 4] \
+[--seed 0] [--out tournament.json]";
+
+const ELO_START: f32 = 1500.0;
+const ELO_K: f32 = 16.0;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Game {
+    // index into `TournamentReport::strategies` of the team playing on the left
+    pub left: usize,
+    pub right: usize,
+    pub seed: u64,
+    pub score: TeamPair<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+pub struct Record {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub goals_for: u32,
+    pub goals_against: u32,
+}
+
+impl Record {
+    fn add(&mut self, goals_for: u32, goals_against: u32) {
+        match goals_for.cmp(&goals_against) {
+            std::cmp::Ordering::Greater => self.wins += 1,
+            std::cmp::Ordering::Equal => self.draws += 1,
+            std::cmp::Ordering::Less => self.losses += 1,
+        }
+        self.goals_for += goals_for;
+        self.goals_against += goals_against;
+    }
+
+    pub fn played(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    pub fn goal_diff(&self) -> i32 {
+        self.goals_for as i32 - self.goals_against as i32
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Standing {
+    pub name: String,
+    pub record: Record,
+    pub elo: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct TournamentReport {
+    pub strategies: Vec<String>,
+    pub games: Vec<Game>,
+    // crosstable[a][b] is the record of a against b, over both sides
+    pub crosstable: Vec<Vec<Record>>,
+    // sorted by rating, best first
+    pub standings: Vec<Standing>,
+}
+
+// Every ordered pairing is played once per seed, so each strategy plays every opponent from both sides.
+// Elo depends on the order games are rated in, so rather than following the round robin it goes
+// through them in an order shuffled with `seed`.
+pub fn run_tournament(conf: &GameConfig, defs: &[&StrategyDef], seeds: u64, seed: u64) -> anyhow::Result<TournamentReport> {
+    let params: Vec<Params> = defs
+        .iter()
+        .map(|def| Params::load_or_default(def.params_path(), def.params))
+        .collect::<anyhow::Result<_>>()?;
+
+    let mut played = Vec::new();
+    for left in 0..defs.len() {
+        for right in 0..defs.len() {
+            if left == right {
+                continue;
+            }
+            for i in 0..seeds {
+                let me = (defs[left].build)(&params[left]);
+                let other = (defs[right].build)(&params[right]);
+                let seed = seed.wrapping_add(i);
+                let result = play_match(conf, &me, &other, seed);
+                played.push((Game { left, right, seed, score: result.score }, result));
+            }
+        }
+    }
+
+    let mut crosstable = vec![vec![Record::default(); defs.len()]; defs.len()];
+    for (game, _) in &played {
+        crosstable[game.left][game.right].add(game.score.me, game.score.other);
+        crosstable[game.right][game.left].add(game.score.other, game.score.me);
+    }
+
+    let mut order: Vec<&(Game, MatchResult)> = played.iter().collect();
+    order.shuffle(&mut StdRng::seed_from_u64(seed));
+    let mut elo = vec![ELO_START; defs.len()];
+    for (game, result) in order {
+        let expected = 1.0 / (1.0 + 10f32.powf((elo[game.right] - elo[game.left]) / 400.0));
+        elo[game.left] += ELO_K * (result.outcome() - expected);
+        elo[game.right] -= ELO_K * (result.outcome() - expected);
+    }
+
+    let mut standings: Vec<Standing> = defs
+        .iter()
+        .enumerate()
+        .map(|(i, def)| {
+            let mut record = Record::default();
+            for cell in &crosstable[i] {
+                record.wins += cell.wins;
+                record.draws += cell.draws;
+                record.losses += cell.losses;
+                record.goals_for += cell.goals_for;
+                record.goals_against += cell.goals_against;
+            }
+            Standing { name: def.name.to_string(), record, elo: elo[i] }
+        })
+        .collect();
+    standings.sort_by(|a, b| b.elo.total_cmp(&a.elo));
+
+    Ok(TournamentReport {
+        strategies: defs.iter().map(|def| def.name.to_string()).collect(),
+        games: played.into_iter().map(|(game, _)| game).collect(),
+        crosstable,
+        standings,
+    })
+}
+
+impl TournamentReport {
+    pub fn to_table(&self) -> String {
+        let width = self.strategies.iter().map(String::len).max().unwrap_or(0).max(8);
+        let mut out = String::new();
+
+        let _ = writeln!(out, "{:<width$}  {:>6}  {:>3}  {:>3}  {:>3}  {:>5}  {:>5}  {:>5}",
+            "strategy", "elo", "w", "d", "l", "gf", "ga", "gd");
+        for standing in &self.standings {
+            let r = &standing.record;
+            let _ = writeln!(out, "{:<width$}  {:>6.0}  {:>3}  {:>3}  {:>3}  {:>5}  {:>5}  {:>+5}",
+                standing.name, standing.elo, r.wins, r.draws, r.losses, r.goals_for, r.goals_against, r.goal_diff());
+        }
+
+        // each cell reads wins-draws-losses (goal difference) of the row against the column
+        let _ = writeln!(out);
+        let _ = write!(out, "{:<width$}", "");
+        for name in &self.strategies {
+            let _ = write!(out, "  {:>width$}", name);
+        }
+        let _ = writeln!(out);
+        for (i, name) in self.strategies.iter().enumerate() {
+            let _ = write!(out, "{:<width$}", name);
+            for (j, cell) in self.crosstable[i].iter().enumerate() {
+                let text = if i == j {
+                    "-".to_string()
+                } else {
+                    format!("{}-{}-{} ({:+})", cell.wins, cell.draws, cell.losses, cell.goal_diff())
+                };
+                let _ = write!(out, "  {:>width$}", text);
+            }
+            let _ = writeln!(out);
+        }
+        out
+    }
+}
+
+pub fn run(args: &[String]) -> anyhow::Result<()> {
+    let args = Args::parse(args)?;
+    let Ok(config_path) = args.positional(0, "config.json") else {
+        println!("{}", USAGE);
+        return Ok(());
+    };
+
+    let conf = load_config(config_path)?;
+    init_config(conf.clone())?;
+
+    let defs = match args.flag_list("strategies") {
+        Some(names) => find_strategies(&names)?,
+        None => STRATEGIES.iter().collect(),
+    };
+    if defs.len() < 2 {
+        anyhow::bail!("a tournament needs at least two strategies");
+    }
+
+    let report = run_tournament(&conf, &defs, args.flag("seeds", 4)?, args.flag("seed", 0)?)?;
+    print!("{}", report.to_table());
+
+    let out = args.flag_str("out").unwrap_or("tournament.json");
+    std::fs::write(out, serde_json::to_string_pretty(&report)?)
+        .with_context(|| format!("unable to write {}", out))?;
+    println!("\nfull results written to {}", out);
+    Ok(())
+}
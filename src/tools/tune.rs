@@ -0,0 +1,181 @@
+// `bot tune`: genetic search over a strategy's declared parameters using self-play
+
+use rand::{ Rng, SeedableRng, rngs::StdRng };
+use std::str::FromStr;
+use crate::core::*;
+use crate::core::sim::play_match;
+use crate::strategy::{ find_strategy, STRATEGIES };
+use crate::strategy::params::*;
+use super::{ Args, find_strategies };
+
+pub const USAGE: &str = "usage: [bin name] tune <strategy> <config.json> [--opponents a,b] \
+[--objective winrate|goaldiff] [--population 16] [--generations 20] [--seeds 4] [--seed 0] [--out path]";
+
+const ELITES: usize = 2;
+const TOURNAMENT_SIZE: usize = 3;
+const MUTATION_SIGMA: f32 = 0.1;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Objective {
+    WinRate,
+    GoalDiff,
+}
+
+impl FromStr for Objective {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "winrate" => Ok(Objective::WinRate),
+            "goaldiff" => Ok(Objective::GoalDiff),
+            _ => anyhow::bail!("unknown objective {}, expected winrate or goaldiff", s),
+        }
+    }
+}
+
+pub struct TuneOptions {
+    pub opponents: Vec<&'static StrategyDef>,
+    pub objective: Objective,
+    pub population: usize,
+    pub generations: usize,
+    pub seeds: u64,
+    pub seed: u64,
+}
+
+#[derive(Clone)]
+struct Individual {
+    genes: Vec<f32>,
+    fitness: f32,
+}
+
+fn decode(def: &StrategyDef, genes: &[f32]) -> Params {
+    let mut params = Params::default();
+    for (spec, &gene) in def.params.iter().zip(genes) {
+        params.set(spec.name, spec.denormalize(gene));
+    }
+    params
+}
+
+// Mean objective over every opponent and match seed. All candidates share the same seeds so
+// they are compared on the same pass errors.
+fn evaluate(conf: &GameConfig, def: &StrategyDef, params: &Params, opts: &TuneOptions) -> f32 {
+    let mut total = 0.0;
+    let mut count = 0;
+    for opponent in &opts.opponents {
+        let opponent_params = opponent.default_params();
+        for i in 0..opts.seeds {
+            let me = (def.build)(params);
+            let other = (opponent.build)(&opponent_params);
+            let result = play_match(conf, &me, &other, opts.seed.wrapping_add(i));
+            total += match opts.objective {
+                Objective::WinRate => result.outcome(),
+                Objective::GoalDiff => result.goal_diff() as f32,
+            };
+            count += 1;
+        }
+    }
+    total / count.max(1) as f32
+}
+
+fn select<'a, R: Rng>(population: &'a [Individual], rng: &mut R) -> &'a Individual {
+    (0..TOURNAMENT_SIZE)
+        .map(|_| &population[rng.random_range(0..population.len())])
+        .max_by(|a, b| a.fitness.total_cmp(&b.fitness))
+        .unwrap()
+}
+
+fn breed<R: Rng>(a: &Individual, b: &Individual, rng: &mut R) -> Vec<f32> {
+    let rate = 1.0 / a.genes.len().max(1) as f32;
+    a.genes
+        .iter()
+        .zip(&b.genes)
+        .map(|(&x, &y)| {
+            let t: f32 = rng.random();
+            let mut gene = x + (y - x) * t;
+            if rng.random::<f32>() < rate {
+                gene += sample_gaussian(rng) * MUTATION_SIGMA;
+            }
+            gene.clamp(0.0, 1.0)
+        })
+        .collect()
+}
+
+pub fn tune(conf: &GameConfig, def: &StrategyDef, opts: &TuneOptions) -> (Params, f32) {
+    let mut rng = StdRng::seed_from_u64(opts.seed);
+    let population_size = opts.population.max(ELITES + 1);
+
+    // the hand-picked defaults always take part, so tuning never ends up worse than them
+    let mut genes: Vec<Vec<f32>> = vec![def.params.iter().map(|spec| spec.normalize(spec.default)).collect()];
+    while genes.len() < population_size {
+        genes.push(def.params.iter().map(|_| rng.random()).collect());
+    }
+
+    let mut best = Individual { genes: genes[0].clone(), fitness: f32::NEG_INFINITY };
+    for generation in 0..opts.generations.max(1) {
+        let mut population: Vec<Individual> = genes
+            .into_iter()
+            .map(|genes| {
+                let fitness = evaluate(conf, def, &decode(def, &genes), opts);
+                Individual { genes, fitness }
+            })
+            .collect();
+        population.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+
+        let mean = population.iter().map(|it| it.fitness).sum::<f32>() / population.len() as f32;
+        println!("generation {}: best {:.3}, mean {:.3}", generation, population[0].fitness, mean);
+        if population[0].fitness > best.fitness {
+            best = population[0].clone();
+        }
+
+        genes = population.iter().take(ELITES).map(|it| it.genes.clone()).collect();
+        while genes.len() < population_size {
+            let (a, b) = (select(&population, &mut rng), select(&population, &mut rng));
+            genes.push(breed(a, b, &mut rng));
+        }
+    }
+
+    (decode(def, &best.genes), best.fitness)
+}
+
+pub fn run(args: &[String]) -> anyhow::Result<()> {
+    let args = Args::parse(args)?;
+    let (Ok(name), Ok(config_path)) = (args.positional(0, "strategy"), args.positional(1, "config.json")) else {
+        println!("{}", USAGE);
+        return Ok(());
+    };
+
+    let def = find_strategy(name).ok_or_else(|| anyhow::anyhow!("unknown strategy {}", name))?;
+    if def.params.is_empty() {
+        anyhow::bail!("strategy {} does not declare any parameters", name);
+    }
+
+    let conf = load_config(config_path)?;
+    init_config(conf.clone())?;
+
+    let opponents = match args.flag_list("opponents") {
+        Some(names) => find_strategies(&names)?,
+        None => STRATEGIES.iter().filter(|it| it.name != def.name).collect(),
+    };
+    if opponents.is_empty() {
+        anyhow::bail!("no opponents to tune against");
+    }
+
+    let opts = TuneOptions {
+        opponents,
+        objective: args.flag("objective", Objective::WinRate)?,
+        population: args.flag("population", 16)?,
+        generations: args.flag("generations", 20)?,
+        seeds: args.flag("seeds", 4)?,
+        seed: args.flag("seed", 0)?,
+    };
+
+    let (params, fitness) = tune(&conf, def, &opts);
+    let out = args.flag_str("out").map(str::to_string).unwrap_or_else(|| def.params_path());
+    params.save(&out)?;
+
+    println!("best fitness {:.3}, parameters written to {}", fitness, out);
+    for (name, value) in params.iter() {
+        println!("  {} = {:.4}", name, value);
+    }
+    Ok(())
+}